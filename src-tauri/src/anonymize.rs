@@ -0,0 +1,245 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::converter::OhhHand;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PseudonymStyle {
+    // Player1, Player2, ... in the order players first appear
+    #[default]
+    Sequential,
+    // a salted hash of the real name, stable across files and runs
+    Hashed,
+}
+
+impl PseudonymStyle {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sequential" | "seq" => Some(PseudonymStyle::Sequential),
+            "hashed" | "hash" => Some(PseudonymStyle::Hashed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymizeOptions {
+    #[serde(default)]
+    pub style: PseudonymStyle,
+    // without a salt hashed names can be reversed by anyone who guesses the
+    // real name
+    #[serde(default)]
+    pub salt: Option<String>,
+    // leave the hero's own name alone
+    #[serde(default)]
+    pub keep_hero: bool,
+}
+
+// 64-bit fnv-1a, spelled out so pseudonyms never change with a std upgrade
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+// real player and table names to pseudonyms. built once over every hand that
+// goes out together, so a player keeps the same pseudonym in all of them
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    options: AnonymizeOptions,
+    players: HashMap<String, String>,
+    tables: HashMap<String, String>,
+    // hero names left as they are, in every hand they appear in
+    kept: HashSet<String>,
+}
+
+impl Anonymizer {
+    // hero ids must already be set on the hands for `keep_hero`
+    pub fn new<'a, I>(options: &AnonymizeOptions, hands: I) -> Self
+    where
+        I: IntoIterator<Item = &'a OhhHand> + Clone,
+    {
+        let mut anonymizer = Anonymizer {
+            options: options.clone(),
+            players: HashMap::new(),
+            tables: HashMap::new(),
+            kept: HashSet::new(),
+        };
+        anonymizer.learn(hands);
+        anonymizer
+    }
+
+    // adds names from more hands, numbering new players after the ones
+    // already known
+    pub fn learn<'a, I>(&mut self, hands: I)
+    where
+        I: IntoIterator<Item = &'a OhhHand> + Clone,
+    {
+        if self.options.keep_hero {
+            for h in hands.clone() {
+                let hero = h
+                    .players
+                    .iter()
+                    .find(|p| h.hero_player_id.as_deref() == Some(p.id.as_str()));
+                if let Some(p) = hero {
+                    self.kept.insert(p.name.clone());
+                }
+            }
+        }
+
+        for h in hands {
+            for p in &h.players {
+                if self.kept.contains(&p.name) || self.players.contains_key(&p.name) {
+                    continue;
+                }
+                let pseudonym = self.pseudonym("Player", &p.name, self.players.len());
+                self.players.insert(p.name.clone(), pseudonym);
+            }
+            if !self.tables.contains_key(&h.table_name) {
+                let pseudonym = self.pseudonym("Table", &h.table_name, self.tables.len());
+                self.tables.insert(h.table_name.clone(), pseudonym);
+            }
+        }
+    }
+
+    fn pseudonym(&self, prefix: &str, name: &str, taken: usize) -> String {
+        match self.options.style {
+            PseudonymStyle::Sequential => format!("{}{}", prefix, taken + 1),
+            PseudonymStyle::Hashed => self.hashed(prefix, name),
+        }
+    }
+
+    fn hashed(&self, prefix: &str, name: &str) -> String {
+        let salt = self.options.salt.as_deref().unwrap_or("");
+        let hash = fnv1a(format!("{}\u{0}{}\u{0}{}", salt, prefix, name).as_bytes());
+        format!("{}{:012x}", prefix, hash >> 16)
+    }
+
+    // replaces screen names, display names and the table; everything else,
+    // including player ids, is left as it was
+    pub fn apply(&self, h: &mut OhhHand) {
+        for p in &mut h.players {
+            if self.kept.contains(&p.name) {
+                continue;
+            }
+            // hands the anonymizer was not built from still get a pseudonym
+            p.name = match self.players.get(&p.name) {
+                Some(pseudonym) => pseudonym.clone(),
+                None => self.hashed("Player", &p.name),
+            };
+            p.display = None;
+        }
+
+        h.table_name = match self.tables.get(&h.table_name) {
+            Some(pseudonym) => pseudonym.clone(),
+            None => self.hashed("Table", &h.table_name),
+        };
+        h.table_handle = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(game: &str, table: &str, names: &[&str], hero: Option<usize>) -> OhhHand {
+        let players: Vec<String> = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                format!(
+                    r#"{{"id":{},"seat":{},"name":"{}","display":"{} shown","starting_stack":10}}"#,
+                    i + 1,
+                    i + 1,
+                    n,
+                    n
+                )
+            })
+            .collect();
+        let hero = hero
+            .map(|i| format!(r#""hero_player_id":{},"#, i))
+            .unwrap_or_default();
+        let json = format!(
+            r#"{{"game_number":"{}","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"{}","table_handle":"{}","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,{}"players":[{}],"rounds":[],"pots":[]}}"#,
+            game,
+            table,
+            table,
+            hero,
+            players.join(",")
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_sequential_names_are_shared_across_hands() {
+        let mut hands = vec![
+            hand("1", "Zurich", &["Ann", "Bob"], Some(2)),
+            hand("2", "Zurich", &["Cy", "Ann"], None),
+            hand("3", "Oslo", &["Bob", "Cy"], None),
+        ];
+        let anonymizer = Anonymizer::new(&AnonymizeOptions::default(), &hands);
+        for h in &mut hands {
+            anonymizer.apply(h);
+        }
+
+        let names = |h: &OhhHand| h.players.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&hands[0]), ["Player1", "Player2"]);
+        assert_eq!(names(&hands[1]), ["Player3", "Player1"]);
+        assert_eq!(names(&hands[2]), ["Player2", "Player3"]);
+        assert!(hands
+            .iter()
+            .flat_map(|h| &h.players)
+            .all(|p| p.display.is_none()));
+        assert_eq!(hands[1].table_name, "Table1");
+        assert_eq!(hands[2].table_name, "Table2");
+        assert_eq!(hands[2].table_handle, None);
+
+        let mut anonymizer = Anonymizer::new(
+            &AnonymizeOptions::default(),
+            [&hand("1", "T", &["Ann"], None)],
+        );
+        let mut later = hand("4", "T", &["Dee", "Ann"], None);
+        anonymizer.learn([&later]);
+        anonymizer.apply(&mut later);
+        assert_eq!(names(&later), ["Player2", "Player1"]);
+    }
+
+    #[test]
+    fn test_hashed_names_and_keep_hero() {
+        let options = AnonymizeOptions {
+            style: PseudonymStyle::Hashed,
+            salt: Some("study group".to_string()),
+            keep_hero: true,
+        };
+        let mut first = hand("1", "Zurich", &["Ann", "Bob"], Some(2));
+        let mut second = hand("2", "Zurich", &["Bob", "Ann"], None);
+        let anonymizer = Anonymizer::new(&options, [&first, &second]);
+        anonymizer.apply(&mut first);
+        anonymizer.apply(&mut second);
+
+        assert!(first.players[0].name.starts_with("Player"));
+        assert_eq!(first.players[0].name.len(), "Player".len() + 12);
+        assert_eq!(first.players[0].name, second.players[1].name);
+        assert_eq!(first.players[1].name, "Bob");
+        assert_eq!(first.players[1].display.as_deref(), Some("Bob shown"));
+        assert_eq!(second.players[0].name, "Bob");
+
+        // a later run with the same salt agrees, another salt does not
+        let mut again = hand("3", "Oslo", &["Ann"], None);
+        Anonymizer::new(&options, [&again]).apply(&mut again);
+        assert_eq!(again.players[0].name, second.players[1].name);
+
+        let unsalted = AnonymizeOptions {
+            style: PseudonymStyle::Hashed,
+            ..Default::default()
+        };
+        let mut third = hand("3", "Zurich", &["Ann"], None);
+        Anonymizer::new(&unsalted, [&third]).apply(&mut third);
+        assert_ne!(third.players[0].name, second.players[1].name);
+    }
+}
@@ -0,0 +1,631 @@
+use log::{debug, error, info, warn};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::anonymize::Anonymizer;
+use crate::converter::{
+    convert_prepared_hands, parse_ohh_chunks, prepare_inputs, ConvertOptions, OhhHand,
+};
+use crate::diagnostics::Diagnostic;
+use crate::filter::FilterCount;
+use crate::split::{part_file_name, split_output, HandSpan, OutputPart};
+use crate::watch::is_ohh_file;
+
+const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100MB
+
+// reads a hand history the user picked, refusing anything that is not an
+// existing .ohh, .txt or .json file of a sane size
+pub fn read_hand_file(path: &Path) -> Result<String, String> {
+    // Ensure the path is absolute and doesn't contain directory traversal
+    let canonical = path.canonicalize().map_err(|e| {
+        error!("Failed to canonicalize path: {}", e);
+        "Invalid file path or file does not exist".to_string()
+    })?;
+
+    debug!("Canonical path: {:?}", canonical);
+
+    // Verify file extension
+    if let Some(ext) = canonical.extension() {
+        let ext_str = ext.to_str().unwrap_or("");
+        debug!("File extension: {}", ext_str);
+        if !matches!(ext_str, "ohh" | "txt" | "json") {
+            let err = "Invalid file type. Only .ohh, .txt, or .json files are supported";
+            error!("{}", err);
+            return Err(err.to_string());
+        }
+    } else {
+        let err = "File must have an extension";
+        error!("{}", err);
+        return Err(err.to_string());
+    }
+
+    // Check file size before reading (prevent DoS)
+    let metadata = fs::metadata(&canonical).map_err(|e| {
+        error!("Failed to get file metadata: {}", e);
+        "Cannot access file".to_string()
+    })?;
+
+    debug!("File size: {} bytes", metadata.len());
+
+    if metadata.len() > MAX_FILE_SIZE {
+        let err = format!(
+            "File too large: {} MB (maximum 100 MB)",
+            metadata.len() / 1024 / 1024
+        );
+        warn!("{}", err);
+        return Err(err);
+    }
+
+    debug!("Reading file content");
+    fs::read_to_string(&canonical).map_err(|e| {
+        error!("Failed to read file: {}", e);
+        "Failed to read file".to_string()
+    })
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOptions {
+    // one output for all inputs instead of one per input
+    #[serde(default)]
+    pub combine: bool,
+    // write the results here; without it the combined text is returned
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub file: String,
+    pub done: usize,
+    pub total: usize,
+    pub hands: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    pub file: String,
+    // every file written for this input, more than one when splitting
+    pub outputs: Vec<String>,
+    pub hands: usize,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub text: String,
+    #[serde(skip)]
+    pub spans: Vec<HandSpan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    pub files: Vec<FileResult>,
+    pub hands: usize,
+    pub failed: usize,
+    pub outputs: Vec<String>,
+    pub output: String,
+    // `output` cut up by the split options, when it was not written to disk
+    pub parts: Vec<OutputPart>,
+    // what was merged or dropped for every hand found more than once
+    pub duplicates: Vec<String>,
+}
+
+// files as given plus the hand histories directly inside any folder, in a
+// stable order and without repeats
+pub fn collect_paths(paths: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for p in paths {
+        let path = PathBuf::from(p);
+        if path.is_dir() {
+            let mut inside: Vec<PathBuf> = fs::read_dir(&path)
+                .map(|entries| entries.flatten().map(|e| e.path()).collect())
+                .unwrap_or_default();
+            inside.retain(|f| f.is_file() && is_ohh_file(f));
+            inside.sort();
+            files.extend(inside);
+        } else {
+            files.push(path);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    files
+}
+
+// the name each file's output starts with: its stem, or the stem plus "-2",
+// "-3" and so on when an earlier file has it, since files from different
+// folders or with different extensions would otherwise overwrite each other
+pub fn output_stems<S: AsRef<str>>(stems: &[S]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    stems
+        .iter()
+        .map(|stem| {
+            let stem = stem.as_ref();
+            let mut name = stem.to_string();
+            let mut n = 2;
+            // some file systems ignore case
+            while !taken.insert(name.to_lowercase()) {
+                name = format!("{}-{}", stem, n);
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+type Parsed = Result<(Vec<OhhHand>, Vec<Diagnostic>), String>;
+
+fn parse_one(path: &Path) -> Parsed {
+    let content = read_hand_file(path)?;
+    parse_ohh_chunks(&content).map_err(|e| e.to_string())
+}
+
+// `count` is how many of the file's hands were left after merging duplicates
+// and how many of those matched the filter
+fn convert_one(
+    path: &Path,
+    parsed: Parsed,
+    count: FilterCount,
+    options: &ConvertOptions,
+    anonymizer: Option<&Anonymizer>,
+) -> FileResult {
+    let mut result = FileResult {
+        file: path.to_string_lossy().into_owned(),
+        outputs: Vec::new(),
+        hands: 0,
+        warnings: Vec::new(),
+        error: None,
+        text: String::new(),
+        spans: Vec::new(),
+    };
+
+    // nothing left to convert is not an error
+    if let Ok((hands, diagnostics)) = &parsed {
+        if hands.is_empty() {
+            result.warnings = diagnostics.iter().map(|d| d.to_string()).collect();
+            result.warnings.push(if count.total > 0 {
+                "no hands match the filter".to_string()
+            } else {
+                "every hand is a duplicate of one in an earlier file".to_string()
+            });
+            return result;
+        }
+    }
+
+    match parsed.and_then(|(hands, diagnostics)| {
+        convert_prepared_hands(hands, diagnostics, options, anonymizer).map_err(|e| e.to_string())
+    }) {
+        Ok(report) => {
+            result.hands = report.hands.len();
+            result.warnings = report.warnings;
+            result.text = report.output;
+            result.spans = report.spans;
+        }
+        Err(e) => {
+            warn!("{}: {}", result.file, e);
+            result.error = Some(e);
+        }
+    }
+    result
+}
+
+// writes `text` into `dir` as `stem` plus the dialect's extension, or as one
+// numbered or keyed file per part when `options` asks for a split
+fn write_outputs(
+    dir: &Path,
+    stem: &str,
+    text: &str,
+    spans: &[HandSpan],
+    options: &ConvertOptions,
+) -> Result<Vec<String>, String> {
+    let extension = format!("{}.txt", options.dialect.name());
+    let separator = options.dialect.writer().separator();
+    let parts = match &options.split {
+        Some(split) => split_output(text, spans, separator, split),
+        None => Vec::new(),
+    };
+    let files: Vec<(PathBuf, &str)> = if options.split.is_some() {
+        parts
+            .iter()
+            .map(|p| (dir.join(part_file_name(stem, &p.key, &extension)), p.output.as_str()))
+            .collect()
+    } else {
+        vec![(dir.join(format!("{}.{}", stem, extension)), text)]
+    };
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, text) in files {
+        fs::write(&path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+    Ok(written)
+}
+
+// converts every file on the rayon pool, reporting each one as it finishes.
+// results keep the order of `paths`. all files are parsed before any is
+// written so pseudonyms and an "auto" hero can be shared across the batch and
+// a hand exported into several files is only converted once
+pub fn convert_batch<F>(
+    paths: &[String],
+    options: &ConvertOptions,
+    batch: &BatchOptions,
+    on_progress: F,
+) -> Result<BatchSummary, String>
+where
+    F: Fn(BatchProgress) + Sync,
+{
+    let files = collect_paths(paths);
+    if files.is_empty() {
+        return Err("no hand history files to convert".to_string());
+    }
+    info!("converting {} files", files.len());
+
+    let output_dir = match &batch.output_dir {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+            Some(dir)
+        }
+        None => None,
+    };
+
+    let mut parsed: Vec<Parsed> = files.par_iter().map(|path| parse_one(path)).collect();
+    // the hero and duplicates are decided over the whole batch
+    let (indices, mut inputs): (Vec<usize>, Vec<_>) = parsed
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, p)| p.as_mut().ok().map(|(hands, diagnostics)| (i, (hands, diagnostics))))
+        .unzip();
+    let mut counts = vec![FilterCount { total: 0, matched: 0 }; files.len()];
+    for (i, count) in indices
+        .into_iter()
+        .zip(prepare_inputs(&mut inputs, options).map_err(|e| e.to_string())?)
+    {
+        counts[i] = count;
+    }
+    let duplicates: Vec<String> = parsed
+        .iter()
+        .filter_map(|p| p.as_ref().ok())
+        .flat_map(|(_, diagnostics)| diagnostics)
+        .filter(|d| matches!(d, Diagnostic::DuplicateHand { .. }))
+        .map(|d| d.to_string())
+        .collect();
+    let anonymizer = options.anonymize.as_ref().map(|anonymize| {
+        let hands = parsed
+            .iter()
+            .filter_map(|p| p.as_ref().ok())
+            .flat_map(|(hands, _)| hands);
+        Anonymizer::new(anonymize, hands)
+    });
+
+    // parts are cut when writing, from each file or from the combined text
+    let per_file = ConvertOptions {
+        split: None,
+        ..options.clone()
+    };
+    let stems: Vec<String> = files
+        .iter()
+        .map(|path| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "hands".to_string())
+        })
+        .collect();
+    let stems = output_stems(&stems);
+    let total = files.len();
+    let done = AtomicUsize::new(0);
+    let mut results: Vec<FileResult> = files
+        .par_iter()
+        .zip(&stems)
+        .zip(parsed)
+        .zip(counts)
+        .map(|(((path, stem), parsed), count)| {
+            let mut result = convert_one(path, parsed, count, &per_file, anonymizer.as_ref());
+
+            let written = result.error.is_none() && result.hands > 0;
+            if let (Some(dir), false, true) = (&output_dir, batch.combine, written) {
+                match write_outputs(dir, stem, &result.text, &result.spans, options) {
+                    Ok(outputs) => result.outputs = outputs,
+                    Err(e) => result.error = Some(e),
+                }
+            }
+
+            on_progress(BatchProgress {
+                file: result.file.clone(),
+                done: done.fetch_add(1, Ordering::SeqCst) + 1,
+                total,
+                hands: result.hands,
+                error: result.error.clone(),
+            });
+            result
+        })
+        .collect();
+
+    let separator = options.dialect.writer().separator();
+    let mut output = String::new();
+    let mut spans = Vec::new();
+    for r in results.iter().filter(|r| r.error.is_none() && r.hands > 0) {
+        if !output.is_empty() {
+            output.push_str(separator);
+        }
+        let offset = output.len();
+        output.push_str(&r.text);
+        spans.extend(r.spans.iter().map(|s| HandSpan {
+            start: s.start + offset,
+            end: s.end + offset,
+            ..s.clone()
+        }));
+    }
+    let mut outputs: Vec<String> = results.iter().flat_map(|r| r.outputs.clone()).collect();
+
+    if let (Some(dir), true) = (&output_dir, batch.combine) {
+        if !output.is_empty() {
+            outputs.extend(write_outputs(dir, "converted_hands", &output, &spans, options)?);
+        }
+    }
+    let parts = match (&output_dir, &options.split) {
+        (None, Some(split)) => split_output(&output, &spans, separator, split),
+        _ => Vec::new(),
+    };
+    // nothing to hand back when every result went to disk
+    if output_dir.is_some() {
+        output.clear();
+    }
+    for r in &mut results {
+        r.text.clear();
+        r.spans.clear();
+    }
+
+    let summary = BatchSummary {
+        hands: results.iter().map(|r| r.hands).sum(),
+        failed: results.iter().filter(|r| r.error.is_some()).count(),
+        files: results,
+        outputs,
+        output,
+        parts,
+        duplicates,
+    };
+    info!(
+        "converted {} hands from {} files, {} failed",
+        summary.hands,
+        summary.files.len(),
+        summary.failed
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anonymize::AnonymizeOptions;
+    use crate::fixtures::{self, hand_json, FOLDED};
+    use crate::split::SplitOptions;
+    use std::sync::Mutex;
+
+    // a scratch folder holding a week of sessions and a stray note
+    fn week_dir(name: &str) -> PathBuf {
+        let dir = fixtures::scratch_dir("batch", name);
+        fs::create_dir_all(dir.join("week")).unwrap();
+        for (file, games) in [("mon.ohh", ["m1", "m2"]), ("tue.ohh", ["t1", "t2"])] {
+            let hands: Vec<String> = games.iter().map(|g| hand_json(FOLDED, g)).collect();
+            fs::write(dir.join("week").join(file), hands.join("\n")).unwrap();
+        }
+        fs::write(dir.join("week").join("notes.md"), "not a hand").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_folder_to_one_file_per_input() {
+        let dir = week_dir("split");
+        let missing = dir.join("gone.ohh").to_string_lossy().into_owned();
+        let paths = vec![dir.join("week").to_string_lossy().into_owned(), missing];
+        let batch = BatchOptions {
+            combine: false,
+            output_dir: Some(dir.join("out").to_string_lossy().into_owned()),
+        };
+
+        let progress = Mutex::new(Vec::new());
+        let summary = convert_batch(&paths, &ConvertOptions::default(), &batch, |p| {
+            progress.lock().unwrap().push(p.done)
+        })
+        .unwrap();
+
+        let mut done = progress.into_inner().unwrap();
+        done.sort();
+        assert_eq!(done, vec![1, 2, 3]);
+
+        assert_eq!(summary.hands, 4);
+        assert_eq!(summary.failed, 1);
+        assert!(summary.files[0].file.ends_with("mon.ohh"));
+        assert!(summary.files[2].error.is_some());
+        assert_eq!(summary.outputs.len(), 2);
+        assert!(summary.output.is_empty());
+
+        let tue = fs::read_to_string(dir.join("out").join("tue.pokerstars.txt")).unwrap();
+        assert!(tue.starts_with("PokerStars Hand #t1"));
+        assert!(tue.contains("PokerStars Hand #t2"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_inputs_with_the_same_stem() {
+        let dir = week_dir("stems");
+        fs::create_dir_all(dir.join("other")).unwrap();
+        fs::write(dir.join("other/mon.ohh"), hand_json(FOLDED, "o1")).unwrap();
+        fs::write(dir.join("week/mon.json"), hand_json(FOLDED, "j1")).unwrap();
+        let paths: Vec<String> = ["week/mon.ohh", "other/mon.ohh", "week/mon.json"]
+            .iter()
+            .map(|p| dir.join(p).to_string_lossy().into_owned())
+            .collect();
+        let batch = BatchOptions {
+            combine: false,
+            output_dir: Some(dir.join("out").to_string_lossy().into_owned()),
+        };
+
+        let summary = convert_batch(&paths, &ConvertOptions::default(), &batch, |_| {}).unwrap();
+        assert_eq!(summary.failed, 0);
+        let out = |name: &str| fs::read_to_string(dir.join("out").join(name)).unwrap();
+        assert!(out("mon.pokerstars.txt").contains("#m2"));
+        assert!(out("mon-2.pokerstars.txt").starts_with("PokerStars Hand #o1"));
+        assert!(out("mon-3.pokerstars.txt").starts_with("PokerStars Hand #j1"));
+        assert_eq!(summary.outputs.len(), 3);
+        assert_eq!(output_stems(&["s", "S", "stdin", "s"]), ["s", "S-2", "stdin", "s-3"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_combined_output() {
+        let dir = week_dir("combine");
+        let paths = vec![
+            dir.join("week/tue.ohh").to_string_lossy().into_owned(),
+            dir.join("week/mon.ohh").to_string_lossy().into_owned(),
+        ];
+        let batch = BatchOptions {
+            combine: true,
+            output_dir: None,
+        };
+
+        let summary = convert_batch(&paths, &ConvertOptions::default(), &batch, |_| {}).unwrap();
+        let order: Vec<usize> = ["#t1", "#t2", "#m1", "#m2"]
+            .iter()
+            .map(|g| summary.output.find(g).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+        assert!(summary.outputs.is_empty());
+
+        // one set of pseudonyms for the whole batch
+        let options = ConvertOptions {
+            anonymize: Some(AnonymizeOptions::default()),
+            ..Default::default()
+        };
+        let summary = convert_batch(&paths, &options, &batch, |_| {}).unwrap();
+        assert!(!summary.output.contains("Ann") && !summary.output.contains("Bob"));
+        assert_eq!(summary.output.matches("Player2: posts big blind").count(), 4);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_overlapping_exports() {
+        let dir = week_dir("overlap");
+        let overlap = [hand_json(FOLDED, "m2"), hand_json(FOLDED, "t1")];
+        fs::write(dir.join("week/all.ohh"), overlap.join("\n")).unwrap();
+        fs::write(dir.join("week/again.ohh"), hand_json(FOLDED, "t2")).unwrap();
+        let batch = BatchOptions {
+            combine: true,
+            output_dir: None,
+        };
+
+        let paths = vec![dir.join("week").to_string_lossy().into_owned()];
+        let summary = convert_batch(&paths, &ConvertOptions::default(), &batch, |_| {}).unwrap();
+        assert_eq!(summary.hands, 4);
+        assert_eq!(summary.failed, 0);
+        for game in ["#m1", "#m2", "#t1", "#t2"] {
+            assert_eq!(summary.output.matches(game).count(), 1);
+        }
+
+        // files go in name order, so the copies in mon and tue are dropped
+        let names: Vec<&str> = summary.files.iter().map(|f| f.file.as_str()).collect();
+        assert!(names[0].ends_with("again.ohh") && names[1].ends_with("all.ohh"));
+        assert_eq!(
+            summary.files[1].warnings[..2],
+            ["hand m2: dropped 1 duplicate copy", "hand t1: dropped 1 duplicate copy"]
+        );
+        assert_eq!(summary.files[3].hands, 0);
+        assert_eq!(summary.duplicates.len(), 3);
+        assert!(summary.files[3].warnings[0].contains("earlier file"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hero_and_filter_across_files() {
+        let dir = week_dir("hero");
+        let cy = hand_json(FOLDED, "w1").replace("Ann", "Cy");
+        fs::write(dir.join("week/wed.ohh"), cy).unwrap();
+        let paths = vec![dir.join("week").to_string_lossy().into_owned()];
+        let batch = BatchOptions {
+            combine: true,
+            output_dir: None,
+        };
+
+        // no file has a clear hero of its own, the batch has Bob in every hand
+        let options = |filter: &str| ConvertOptions {
+            hero: Some("auto".to_string()),
+            filter: Some(filter.to_string()),
+            ..Default::default()
+        };
+        let summary = convert_batch(&paths, &options("won"), &batch, |_| {}).unwrap();
+        assert_eq!(summary.hands, 5);
+        assert_eq!(summary.failed, 0);
+
+        let summary = convert_batch(&paths, &options("won and player Cy"), &batch, |_| {}).unwrap();
+        assert_eq!(summary.hands, 1);
+        assert_eq!(summary.failed, 0);
+        assert_eq!(summary.files[0].hands, 0);
+        assert_eq!(summary.files[0].warnings, ["no hands match the filter"]);
+        assert_eq!(summary.files[2].hands, 1);
+
+        assert!(convert_batch(&paths, &options("won and"), &batch, |_| {}).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_split_outputs() {
+        let dir = week_dir("parts");
+        let paths = vec![dir.join("week").to_string_lossy().into_owned()];
+        let options = ConvertOptions {
+            split: Some(SplitOptions {
+                max_hands: Some(1),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let batch = BatchOptions {
+            combine: false,
+            output_dir: Some(dir.join("out").to_string_lossy().into_owned()),
+        };
+
+        let summary = convert_batch(&paths, &options, &batch, |_| {}).unwrap();
+        assert_eq!(summary.files[0].outputs.len(), 2);
+        assert_eq!(summary.outputs.len(), 4);
+        let second = fs::read_to_string(dir.join("out/mon_002.pokerstars.txt")).unwrap();
+        assert!(second.starts_with("PokerStars Hand #m2") && !second.contains("#m1"));
+
+        // without an output folder the parts come back for the zip download
+        let batch = BatchOptions {
+            combine: true,
+            output_dir: None,
+        };
+        let options = ConvertOptions {
+            split: Some(SplitOptions {
+                max_hands: Some(3),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let summary = convert_batch(&paths, &options, &batch, |_| {}).unwrap();
+        let parts: Vec<(&str, usize)> = summary
+            .parts
+            .iter()
+            .map(|p| (p.key.as_str(), p.hands.len()))
+            .collect();
+        assert_eq!(parts, [("001", 3), ("002", 1)]);
+        assert!(summary.parts[1].output.starts_with("PokerStars Hand #t2"));
+        assert_eq!(
+            summary.parts[0].output.len() + summary.parts[1].output.len() + 4,
+            summary.output.len()
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
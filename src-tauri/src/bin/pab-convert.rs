@@ -0,0 +1,496 @@
+use clap::Parser;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use pab_converter_lib::anonymize::{AnonymizeOptions, Anonymizer, PseudonymStyle};
+use pab_converter_lib::batch::output_stems;
+use pab_converter_lib::converter::{self, ConvertOptions, OhhHand};
+use pab_converter_lib::diagnostics::{ConvertError, ConvertReport, Diagnostic};
+use pab_converter_lib::filter::Filter;
+use pab_converter_lib::naming::{self, Aliases, NamePolicy};
+use pab_converter_lib::pokerstars;
+use pab_converter_lib::split::{part_file_name, SplitBy, SplitOptions};
+use pab_converter_lib::watch;
+use pab_converter_lib::writer::Dialect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text(Dialect),
+    Ohh,
+}
+
+impl Format {
+    fn extension(self) -> String {
+        match self {
+            Format::Text(dialect) => format!("{}.txt", dialect.name()),
+            Format::Ohh => "ohh".to_string(),
+        }
+    }
+}
+
+fn parse_format(name: &str) -> Result<Format, String> {
+    if name.eq_ignore_ascii_case("ohh") {
+        return Ok(Format::Ohh);
+    }
+    Dialect::parse(name).map(Format::Text).ok_or_else(|| {
+        format!(
+            "unknown format '{}', expected pokerstars, ggpoker, fulltilt or ohh",
+            name
+        )
+    })
+}
+
+fn parse_style(name: &str) -> Result<PseudonymStyle, String> {
+    PseudonymStyle::parse(name)
+        .ok_or_else(|| format!("unknown pseudonym style '{}', expected sequential or hashed", name))
+}
+
+// checked up front so a typo fails before any file is read
+fn parse_filter(text: &str) -> Result<String, String> {
+    Filter::parse(text).map(|_| text.to_string())
+}
+
+fn parse_split_by(name: &str) -> Result<SplitBy, String> {
+    SplitBy::parse(name)
+        .ok_or_else(|| format!("unknown split '{}', expected table, session or day", name))
+}
+
+// bytes, or with a k or m suffix for kilobytes and megabytes
+fn parse_size(text: &str) -> Result<usize, String> {
+    let lower = text.trim().to_ascii_lowercase();
+    let lower = lower.trim_end_matches('b');
+    let (number, scale) = match lower.char_indices().last() {
+        Some((i, 'k')) => (&lower[..i], 1024),
+        Some((i, 'm')) => (&lower[..i], 1024 * 1024),
+        _ => (lower, 1),
+    };
+    match number.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n * scale),
+        _ => Err(format!("'{}' is not a size, expected e.g. 500k or 5m", text)),
+    }
+}
+
+fn parse_names(name: &str) -> Result<NamePolicy, String> {
+    NamePolicy::parse(name).ok_or_else(|| {
+        format!(
+            "unknown name policy '{}', expected name, display or display-or-name",
+            name
+        )
+    })
+}
+
+/// Convert Open Hand History files to text hand histories, or PokerStars
+/// text back to OHH.
+#[derive(Debug, Parser)]
+#[command(name = "pab-convert", version)]
+struct Cli {
+    /// Files or glob patterns to convert; reads stdin when none are given or for "-"
+    inputs: Vec<String>,
+
+    /// Write one output file per input into this directory instead of stdout
+    #[arg(short, long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Output format: pokerstars, ggpoker, fulltilt or ohh (normalized OHH)
+    #[arg(short, long, default_value = "pokerstars", value_parser = parse_format)]
+    format: Format,
+
+    /// Player id, name or display name to treat as the hero, or "auto" for
+    /// the player seated in the most hands
+    #[arg(long)]
+    hero: Option<String>,
+
+    /// Only convert hands matching EXPR, e.g. "saw flop and pot > 50bb",
+    /// "player Bob", "allin or showdown", "date >= 2023-12-05"
+    #[arg(long, value_name = "EXPR", value_parser = parse_filter)]
+    filter: Option<String>,
+
+    /// Which player name to print: name (screen name), display, or
+    /// display-or-name
+    #[arg(long, value_name = "POLICY", default_value = "name", value_parser = parse_names)]
+    names: NamePolicy,
+
+    /// File of `screen name = alias` lines (or a JSON object) with names to
+    /// print instead
+    #[arg(long, value_name = "FILE")]
+    aliases: Option<PathBuf>,
+
+    #[arg(skip)]
+    alias_map: Aliases,
+
+    /// Replace player and table names with pseudonyms, numbered in order of
+    /// appearance (sequential) or derived from the name (hashed)
+    #[arg(
+        long,
+        value_name = "STYLE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "sequential",
+        value_parser = parse_style
+    )]
+    anonymize: Option<PseudonymStyle>,
+
+    /// Salt for hashed pseudonyms, so they cannot be matched to names by guessing
+    #[arg(long, requires = "anonymize")]
+    salt: Option<String>,
+
+    /// Keep the hero's own name when anonymizing
+    #[arg(long, requires = "anonymize")]
+    keep_hero: bool,
+
+    /// Write one file per table, session or day instead of one per input
+    #[arg(long, value_name = "WHAT", requires = "out_dir", value_parser = parse_split_by)]
+    split_by: Option<SplitBy>,
+
+    /// Start a new output file after this many hands
+    #[arg(long, value_name = "N", requires = "out_dir", value_parser = clap::value_parser!(u64).range(1..))]
+    max_hands: Option<u64>,
+
+    /// Start a new output file before it grows past SIZE, e.g. 500k or 5m
+    #[arg(long, value_name = "SIZE", requires = "out_dir", value_parser = parse_size)]
+    max_size: Option<usize>,
+
+    /// Skip hands whose bets, stacks or pots do not add up
+    #[arg(long)]
+    strict: bool,
+
+    /// Do not print warnings
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Keep converting new hands written to this folder into --out-dir
+    #[arg(
+        short,
+        long,
+        value_name = "DIR",
+        requires = "out_dir",
+        conflicts_with = "inputs"
+    )]
+    watch: Option<PathBuf>,
+}
+
+impl Cli {
+    fn options(&self, dialect: Dialect) -> ConvertOptions {
+        ConvertOptions {
+            strict: self.strict,
+            dialect,
+            hero: self.hero.clone(),
+            anonymize: self.anonymize.map(|style| AnonymizeOptions {
+                style,
+                salt: self.salt.clone(),
+                keep_hero: self.keep_hero,
+            }),
+            names: self.names,
+            aliases: self.alias_map.clone(),
+            filter: self.filter.clone(),
+            split: self.split(),
+        }
+    }
+
+    fn split(&self) -> Option<SplitOptions> {
+        let split = SplitOptions {
+            by: self.split_by,
+            max_hands: self.max_hands.map(|n| n as usize),
+            max_bytes: self.max_size,
+        };
+        (split != SplitOptions::default()).then_some(split)
+    }
+
+    fn dialect(&self) -> Dialect {
+        match self.format {
+            Format::Text(dialect) => dialect,
+            Format::Ohh => Dialect::default(),
+        }
+    }
+}
+
+enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    fn label(&self) -> String {
+        match self {
+            Input::Stdin => "<stdin>".to_string(),
+            Input::File(path) => path.display().to_string(),
+        }
+    }
+
+    fn read(&self) -> io::Result<String> {
+        match self {
+            Input::Stdin => {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                Ok(content)
+            }
+            Input::File(path) => fs::read_to_string(path),
+        }
+    }
+
+    fn stem(&self) -> String {
+        match self {
+            Input::Stdin => "stdin".to_string(),
+            Input::File(path) => path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "hands".to_string()),
+        }
+    }
+}
+
+fn expand_inputs(args: &[String]) -> Result<Vec<Input>, String> {
+    if args.is_empty() {
+        return Ok(vec![Input::Stdin]);
+    }
+
+    let mut inputs = Vec::new();
+    for arg in args {
+        if arg == "-" {
+            inputs.push(Input::Stdin);
+        } else if arg.contains(['*', '?', '[']) {
+            let paths = glob::glob(arg).map_err(|e| format!("bad pattern '{}': {}", arg, e))?;
+            let before = inputs.len();
+            for path in paths {
+                let path = path.map_err(|e| e.to_string())?;
+                if path.is_file() {
+                    inputs.push(Input::File(path));
+                }
+            }
+            if inputs.len() == before {
+                return Err(format!("no files match '{}'", arg));
+            }
+        } else {
+            inputs.push(Input::File(PathBuf::from(arg)));
+        }
+    }
+    Ok(inputs)
+}
+
+struct Parsed {
+    hands: Vec<OhhHand>,
+    diagnostics: Vec<Diagnostic>,
+    // pokerstars text, which always goes back to ohh
+    reverse: bool,
+}
+
+fn parse(input: &Input) -> Result<Parsed, String> {
+    let content = input.read().map_err(|e| e.to_string())?;
+    let reverse = pokerstars::is_pokerstars_text(&content);
+    let (hands, diagnostics) = if reverse {
+        pokerstars::parse_pokerstars_file(&content)
+    } else {
+        converter::parse_ohh_chunks(&content)
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(Parsed {
+        hands,
+        diagnostics,
+        reverse,
+    })
+}
+
+// `anonymizer` carries pseudonyms over from the inputs before this one
+fn convert(
+    parsed: Parsed,
+    cli: &Cli,
+    anonymizer: &mut Option<Anonymizer>,
+) -> Result<ConvertReport, ConvertError> {
+    let options = cli.options(cli.dialect());
+    let Parsed {
+        hands,
+        diagnostics,
+        reverse,
+    } = parsed;
+    if let Some(anonymizer) = anonymizer.as_mut() {
+        anonymizer.learn(&hands);
+    }
+
+    match cli.format {
+        Format::Text(_) if !reverse => {
+            converter::convert_prepared_hands(hands, diagnostics, &options, anonymizer.as_ref())
+        }
+        _ => converter::normalize_prepared_hands(hands, diagnostics, &options, anonymizer.as_ref()),
+    }
+}
+
+fn run(cli: &Cli) -> Result<bool, String> {
+    let inputs = expand_inputs(&cli.inputs)?;
+    if let Some(dir) = &cli.out_dir {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+
+    let mut anonymizer = cli
+        .options(cli.dialect())
+        .anonymize
+        .map(|anonymize| Anonymizer::new(&anonymize, std::iter::empty()));
+
+    // everything is read first so a hand in several inputs is converted once,
+    // from the first of them, and "auto" finds one hero for all of them
+    let mut parsed: Vec<Result<Parsed, String>> = inputs.iter().map(parse).collect();
+    let mut prepared: Vec<_> = parsed
+        .iter_mut()
+        .filter_map(|p| p.as_mut().ok())
+        .map(|p| (&mut p.hands, &mut p.diagnostics))
+        .collect();
+    let mut counts = converter::prepare_inputs(&mut prepared, &cli.options(cli.dialect()))
+        .map_err(|e| e.to_string())?
+        .into_iter();
+
+    let stems: Vec<String> = inputs.iter().map(Input::stem).collect();
+    let stems = output_stems(&stems);
+
+    let mut ok = true;
+    let mut stdout = io::stdout().lock();
+    let mut first = true;
+
+    for ((input, stem), parsed) in inputs.iter().zip(&stems).zip(parsed) {
+        let label = input.label();
+        let parsed = match parsed {
+            Ok(parsed) => {
+                let count = counts.next();
+                if !parsed.hands.is_empty() {
+                    parsed
+                } else {
+                    if !cli.quiet {
+                        match count {
+                            Some(count) if count.total > 0 => {
+                                eprintln!("{}: no hands match the filter", label)
+                            }
+                            _ => eprintln!(
+                                "{}: every hand is a duplicate of one in an earlier input",
+                                label
+                            ),
+                        }
+                    }
+                    continue;
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", label, e);
+                ok = false;
+                continue;
+            }
+        };
+
+        // pokerstars text always comes out as ohh, whatever --format says
+        let format = if parsed.reverse { Format::Ohh } else { cli.format };
+        let report = match convert(parsed, cli, &mut anonymizer) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("{}: {}", label, e);
+                ok = false;
+                continue;
+            }
+        };
+
+        if !cli.quiet {
+            for warning in &report.warnings {
+                eprintln!("{}: {}", label, warning);
+            }
+        }
+
+        match &cli.out_dir {
+            Some(dir) => {
+                let extension = format.extension();
+                let files: Vec<(PathBuf, usize, &str)> = if report.parts.is_empty() {
+                    let path = dir.join(format!("{}.{}", stem, extension));
+                    vec![(path, report.hands.len(), report.output.as_str())]
+                } else {
+                    report
+                        .parts
+                        .iter()
+                        .map(|part| {
+                            let name = part_file_name(stem, &part.key, &extension);
+                            (dir.join(name), part.hands.len(), part.output.as_str())
+                        })
+                        .collect()
+                };
+                for (path, hands, text) in files {
+                    fs::write(&path, text)
+                        .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+                    if !cli.quiet {
+                        eprintln!("{}: {} hands -> {}", label, hands, path.display());
+                    }
+                }
+            }
+            None => {
+                let separator: &[u8] = if first { b"" } else { b"\n\n\n" };
+                let written = stdout
+                    .write_all(separator)
+                    .and_then(|_| stdout.write_all(report.output.trim_end().as_bytes()))
+                    .and_then(|_| stdout.write_all(b"\n"));
+                match written {
+                    Ok(()) => {}
+                    // the reader went away, e.g. piped into head
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(ok),
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+        }
+        first = false;
+    }
+
+    Ok(ok)
+}
+
+// runs until interrupted
+fn run_watch(cli: &Cli, input_dir: &Path, output_dir: &Path) -> Result<bool, String> {
+    let dialect = match cli.format {
+        Format::Text(dialect) => dialect,
+        Format::Ohh => return Err("--watch writes text hand histories, not ohh".to_string()),
+    };
+
+    let quiet = cli.quiet;
+    let watcher = watch::watch_dir(input_dir, output_dir, cli.options(dialect), move |update| {
+        if let Some(e) = &update.error {
+            eprintln!("{}: {}", update.file, e);
+            return;
+        }
+        if quiet {
+            return;
+        }
+        for warning in &update.warnings {
+            eprintln!("{}: {}", update.file, warning);
+        }
+        eprintln!(
+            "{}: {} new hands -> {}",
+            update.file,
+            update.hands.len(),
+            update.output
+        );
+    })?;
+
+    eprintln!("watching {}, press ctrl-c to stop", input_dir.display());
+    watcher.wait();
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let mut cli = Cli::parse();
+    if let Some(path) = &cli.aliases {
+        match naming::load_aliases(path) {
+            Ok(aliases) => cli.alias_map = aliases,
+            Err(e) => {
+                eprintln!("pab-convert: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let result = match (&cli.watch, &cli.out_dir) {
+        (Some(input_dir), Some(output_dir)) => run_watch(&cli, input_dir, output_dir),
+        _ => run(&cli),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("pab-convert: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,11 +1,30 @@
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-use log::{debug, info, warn, error};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+
+use crate::anonymize::{AnonymizeOptions, Anonymizer};
+use crate::dedupe::{dedupe_groups, dedupe_hands};
+use crate::diagnostics::{ConvertError, ConvertReport, Diagnostic};
+use crate::filter::{Filter, FilterCount};
+use crate::framing::frame_ohh;
+use crate::money::{Money, MoneyFormat};
+use crate::naming::{Aliases, NamePolicy, PrintedNames};
+use crate::split::{HandSpan, SplitOptions};
+use crate::validate::{validate, Issue};
+use crate::writer::{Dialect, HandHistoryWriter, PokerStarsWriter};
+pub use crate::money::fmt_money;
+
+pub const OHH_SPEC_VERSION: &str = "1.4.6";
 
 fn default_game_number() -> String {
     "unknown".to_string()
 }
 
+// anything the model does not know about is kept in `extra` so that
+// re-exported ohh loses nothing
+pub type Extra = Map<String, Value>;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OhhFile {
     pub ohh: OhhHand,
@@ -14,41 +33,79 @@ pub struct OhhFile {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OhhHand {
     pub spec_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_version: Option<String>,
     #[serde(default = "default_game_number")]
     pub game_number: String,
     pub game_type: Option<String>,
     pub bet_limit: Option<BetLimit>,
-    pub small_blind_amount: f64,
-    pub big_blind_amount: f64,
+    pub small_blind_amount: Money,
+    pub big_blind_amount: Money,
+    pub ante_amount: Option<Money>,
     pub currency: Option<String>,
     pub start_date_utc: String,
     pub table_name: String,
     pub table_size: u8,
     pub table_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_skin: Option<String>,
     pub dealer_seat: u8,
-    #[serde(default, deserialize_with = "deserialize_optional_player_id")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_player_id",
+        serialize_with = "serialize_optional_player_id"
+    )]
     pub hero_player_id: Option<String>,
     pub site_name: Option<String>,
     pub network_name: Option<String>,
+    #[serde(default)]
+    pub tournament: bool,
+    pub tournament_info: Option<TournamentInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
     pub players: Vec<Player>,
     pub rounds: Vec<Round>,
     pub pots: Vec<Pot>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TournamentInfo {
+    #[serde(default, deserialize_with = "deserialize_optional_player_id")]
+    pub tournament_number: Option<String>,
+    pub name: Option<String>,
+    pub currency: Option<String>,
+    pub buyin_amount: Option<Money>,
+    pub fee_amount: Option<Money>,
+    pub bounty_fee_amount: Option<Money>,
+    pub initial_stack: Option<Money>,
+    pub level: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BetLimit {
     pub bet_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Player {
-    #[serde(deserialize_with = "deserialize_player_id")]
+    #[serde(
+        deserialize_with = "deserialize_player_id",
+        serialize_with = "serialize_player_id"
+    )]
     pub id: String,
     pub seat: u8,
     pub name: String,
     pub display: Option<String>,
-    pub starting_stack: f64,
-    pub player_bounty: Option<f64>,
+    pub starting_stack: Money,
+    pub player_bounty: Option<Money>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 fn deserialize_player_id<'de, D>(deserializer: D) -> Result<String, D::Error>
@@ -106,17 +163,25 @@ pub struct Round {
     #[serde(default)]
     pub cards: Vec<String>,
     pub actions: Vec<Action>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Action {
     pub action_number: u32,
-    #[serde(default, deserialize_with = "deserialize_optional_player_id")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_player_id",
+        serialize_with = "serialize_optional_player_id"
+    )]
     pub player_id: Option<String>,
     pub action: String,
-    pub amount: Option<f64>,
+    pub amount: Option<Money>,
     pub is_allin: Option<bool>,
     pub cards: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 fn deserialize_optional_player_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -142,6 +207,14 @@ where
             Ok(None)
         }
 
+        // deserialize_any hands an explicit json null to visit_unit
+        fn visit_unit<E>(self) -> Result<Option<String>, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+
         fn visit_some<D>(self, deserializer: D) -> Result<Option<String>, D::Error>
         where
             D: serde::Deserializer<'de>,
@@ -174,83 +247,176 @@ where
     deserializer.deserialize_any(OptionalPlayerIdVisitor)
 }
 
+// ohh player ids are integers; ids read from strings are written back as
+// numbers only when that loses nothing, so "007" stays a string
+fn serialize_player_id<S: Serializer>(id: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    match numeric_player_id(id) {
+        Some(n) => serializer.serialize_u64(n),
+        None => serializer.serialize_str(id),
+    }
+}
+
+// the number an id stands for, if writing it as one gives back the same id
+fn numeric_player_id(id: &str) -> Option<u64> {
+    id.parse::<u64>().ok().filter(|n| n.to_string() == id)
+}
+
+fn serialize_optional_player_id<S: Serializer>(
+    id: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match id {
+        Some(id) => serialize_player_id(id, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Pot {
     pub number: u8,
-    pub amount: f64,
-    pub rake: f64,
-    pub jackpot: Option<f64>,
+    pub amount: Money,
+    pub rake: Money,
+    pub jackpot: Option<Money>,
     pub player_wins: Vec<PlayerWin>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PlayerWin {
-    #[serde(deserialize_with = "deserialize_player_id")]
+    #[serde(
+        deserialize_with = "deserialize_player_id",
+        serialize_with = "serialize_player_id"
+    )]
     pub player_id: String,
-    pub win_amount: f64,
-    pub contributed_rake: Option<f64>,
+    pub win_amount: Money,
+    pub contributed_rake: Option<Money>,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-pub fn parse_ohh_chunks(text: &str) -> Result<Vec<OhhHand>, String> {
+pub fn parse_ohh_chunks(text: &str) -> Result<(Vec<OhhHand>, Vec<Diagnostic>), ConvertError> {
     debug!("parse_ohh_chunks called with {} bytes", text.len());
     let mut hands = Vec::new();
+    let mut diagnostics = Vec::new();
 
-    for (idx, chunk) in text.split("\n\n").enumerate() {
-        let preview = &chunk.chars().take(200).collect::<String>();
-
-        match serde_json::from_str::<OhhFile>(chunk) {
-            Ok(data) => {
-                debug!("parsed OhhFile chunk {}", idx);
-                hands.extend(data.hands);
+    for record in frame_ohh(text) {
+        match record {
+            Ok(framed) => {
+                debug!("parsed hand record {} at byte {}", framed.index, framed.offset);
+                hands.push(framed.hand);
+            }
+            Err(e) => {
+                warn!(
+                    "record {} at byte {} (line {}, column {}): failed to parse: {}",
+                    e.index, e.offset, e.line, e.column, e.message
+                );
+                diagnostics.push(Diagnostic::from(e));
             }
-            Err(e1) => match serde_json::from_str::<OhhHand>(chunk) {
-                Ok(hand) => {
-                    debug!("parsed OhhHand chunk {}", idx);
-                    hands.push(hand);
-                }
-                Err(e2) => {
-                    warn!(
-                        "chunk {}: failed to parse (OhhFile: {}, OhhHand: {}). preview: {}",
-                        idx, e1, e2, preview
-                    );
-                }
-            },
         }
     }
 
     if hands.is_empty() {
-        return Err("no valid hands could be parsed. please check your file format.".to_string());
+        return Err(ConvertError::NoHands(diagnostics));
     }
 
-    Ok(hands)
+    Ok((hands, diagnostics))
 }
 
-pub fn fmt_money(x: f64) -> String {
-    let abs_val = x.abs();
-    let dollars = abs_val as i64;
-    let cents = ((abs_val - dollars as f64) * 100.0).round() as i64;
+pub fn is_tournament(h: &OhhHand) -> bool {
+    h.tournament || h.tournament_info.is_some()
+}
 
-    let dollars_str = if dollars >= 1000 {
-        let mut result = String::new();
-        let s = dollars.to_string();
-        let len = s.len();
-        for (i, c) in s.chars().enumerate() {
-            if i > 0 && (len - i) % 3 == 0 {
-                result.push(',');
-            }
-            result.push(c);
+// tournament stacks and bets are plain chip counts, cash games use the
+// hand's currency
+pub fn money_format(h: &OhhHand) -> MoneyFormat {
+    if is_tournament(h) {
+        MoneyFormat::chips()
+    } else {
+        MoneyFormat::for_currency(h.currency.as_deref().unwrap_or("USD"))
+    }
+}
+
+fn tournament_currency(h: &OhhHand) -> &str {
+    h.tournament_info
+        .as_ref()
+        .and_then(|t| t.currency.as_deref())
+        .or(h.currency.as_deref())
+        .unwrap_or("USD")
+}
+
+pub fn amount(h: &OhhHand, x: Money) -> String {
+    money_format(h).format(x)
+}
+
+pub fn roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
         }
-        result
+    }
+    out
+}
+
+fn tournament_header(h: &OhhHand) -> String {
+    let info = h.tournament_info.as_ref();
+    let number = info
+        .and_then(|t| t.tournament_number.clone())
+        .unwrap_or_else(|| "0".to_string());
+    let cur = tournament_currency(h);
+    let money = MoneyFormat::for_currency(cur);
+
+    let buyin = info.and_then(|t| t.buyin_amount).unwrap_or_default();
+    let fee = info.and_then(|t| t.fee_amount).unwrap_or_default();
+    let bounty = info.and_then(|t| t.bounty_fee_amount).unwrap_or_default();
+
+    let cost = if (buyin + fee + bounty).is_zero() {
+        "Freeroll".to_string()
+    } else if bounty.is_positive() {
+        format!(
+            "{}+{}+{} {}",
+            money.format(buyin),
+            money.format(bounty),
+            money.format(fee),
+            cur
+        )
     } else {
-        dollars.to_string()
+        format!("{}+{} {}", money.format(buyin), money.format(fee), cur)
     };
 
-    let formatted = format!("${}.{:02}", dollars_str, cents);
-    if x < 0.0 {
-        format!("-{}", formatted)
-    } else {
-        formatted
-    }
+    let level = info
+        .and_then(|t| t.level)
+        .map(|l| format!(" - Level {}", roman(l)))
+        .unwrap_or_default();
+
+    format!(
+        "Tournament #{}, {} {} {}{} ({}/{})",
+        number,
+        cost,
+        game_name(h),
+        limit_name(h),
+        level,
+        amount(h, h.small_blind_amount),
+        amount(h, h.big_blind_amount)
+    )
 }
 
 pub fn card(c: &str) -> String {
@@ -265,10 +431,67 @@ pub fn cards(board: &[String]) -> String {
     board.iter().map(|c| card(c)).collect::<Vec<_>>().join(" ")
 }
 
+fn is_omaha(h: &OhhHand) -> bool {
+    h.game_type
+        .as_deref()
+        .map(|g| g.to_lowercase().contains("omaha"))
+        .unwrap_or(false)
+}
+
+pub fn hole_card_count(h: &OhhHand) -> usize {
+    h.rounds
+        .iter()
+        .flat_map(|r| &r.actions)
+        .filter_map(|a| a.cards.as_ref().map(|c| c.len()))
+        .max()
+        .unwrap_or(2)
+}
+
+pub fn game_name(h: &OhhHand) -> String {
+    let normalized: String = h
+        .game_type
+        .as_deref()
+        .unwrap_or("Holdem")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+
+    let game = match normalized.as_str() {
+        "holdem" | "texasholdem" => "Hold'em".to_string(),
+        "omaha" | "omahahi" | "plo" => "Omaha".to_string(),
+        "omahahilo" | "omahahl" | "omaha8" | "omahahighlow" => "Omaha Hi/Lo".to_string(),
+        _ => h.game_type.clone().unwrap_or_default(),
+    };
+
+    if is_omaha(h) && hole_card_count(h) == 5 {
+        format!("5 Card {}", game)
+    } else {
+        game
+    }
+}
+
+pub fn limit_name(h: &OhhHand) -> &'static str {
+    let bet_type = h.bet_limit.as_ref().and_then(|b| b.bet_type.as_deref());
+    match bet_type.map(|t| t.to_uppercase()) {
+        Some(t) if t == "PL" => "Pot Limit",
+        Some(t) if t == "FL" => "Limit",
+        _ => "No Limit",
+    }
+}
+
+pub fn describe_hand(h: &OhhHand, hole: &[String], board: &[String]) -> Option<String> {
+    if is_omaha(h) && board.len() >= 3 {
+        crate::hand_rank::describe_omaha(hole, board)
+    } else {
+        crate::hand_rank::describe_cards(hole, board)
+    }
+}
+
 pub fn build_header(h: &OhhHand) -> String {
     let game_num = &h.game_number;
-    let sb = fmt_money(h.small_blind_amount);
-    let bb = fmt_money(h.big_blind_amount);
+    let sb = amount(h, h.small_blind_amount);
+    let bb = amount(h, h.big_blind_amount);
     let cur = h.currency.as_deref().unwrap_or("USD");
 
     let ts = h
@@ -276,11 +499,33 @@ pub fn build_header(h: &OhhHand) -> String {
         .split('.')
         .next()
         .unwrap_or(&h.start_date_utc)
+        .trim_end_matches('Z')
         .replace('T', " ");
 
+    if is_tournament(h) {
+        return format!(
+            "PokerStars Hand #{}: {} - {} UTC",
+            game_num,
+            tournament_header(h),
+            ts
+        );
+    }
+
+    let ante = match h.ante_amount {
+        Some(a) if a.is_positive() => format!(" - Ante {}", amount(h, a)),
+        _ => String::new(),
+    };
+
     format!(
-        "PokerStars Hand #{}: Hold'em No Limit ({}/{} {}) - {} UTC",
-        game_num, sb, bb, cur, ts
+        "PokerStars Hand #{}: {} {} ({}/{}{} {}) - {} UTC",
+        game_num,
+        game_name(h),
+        limit_name(h),
+        sb,
+        bb,
+        ante,
+        cur,
+        ts
     )
 }
 
@@ -298,11 +543,19 @@ pub fn build_seats(h: &OhhHand) -> String {
     players
         .iter()
         .map(|p| {
+            let bounty = match p.player_bounty {
+                Some(b) if b.is_positive() && is_tournament(h) => format!(
+                    ", {} bounty",
+                    MoneyFormat::for_currency(tournament_currency(h)).format(b)
+                ),
+                _ => String::new(),
+            };
             format!(
-                "Seat {}: {} ({} in chips)",
+                "Seat {}: {} ({} in chips{})",
                 p.seat,
                 p.name,
-                fmt_money(p.starting_stack)
+                amount(h, p.starting_stack),
+                bounty
             )
         })
         .collect::<Vec<_>>()
@@ -352,15 +605,160 @@ pub fn street_header(street: &str, brd: &[String], all_board: &[String]) -> Stri
     }
 }
 
+pub fn is_known_action(action: &str) -> bool {
+    is_post(action)
+        || matches!(
+            action,
+            "Dealt Cards" | "Fold" | "Check" | "Call" | "Bet" | "Raise" | "Shows Cards" | "Muck"
+        )
+}
+
+pub fn is_post(action: &str) -> bool {
+    matches!(
+        action,
+        "Post SB" | "Post BB" | "Post Ante" | "Post Dead" | "Post Extra Blind" | "Straddle"
+    )
+}
+
+// the part of a post that counts towards calling the current bet; antes go
+// straight into the pot
+pub fn live_post_amount(h: &OhhHand, action: &str, amt: Money) -> Money {
+    match action {
+        "Post Ante" => Money::ZERO,
+        "Post Dead" => live_dead_amount(h.small_blind_amount, h.big_blind_amount, amt),
+        _ => amt,
+    }
+}
+
+// a dead small blind goes straight into the pot; of "small & big blinds" the
+// big blind is live
+pub fn live_dead_amount(sb: Money, bb: Money, amt: Money) -> Money {
+    if amt >= sb + bb {
+        amt - sb
+    } else if amt < bb {
+        Money::ZERO
+    } else {
+        amt
+    }
+}
+
+fn post_blinds_line(h: &OhhHand, n: &str, amt: Money) -> String {
+    let both = h.small_blind_amount + h.big_blind_amount;
+    if amt >= both {
+        format!("{}: posts small & big blinds {}", n, amount(h, amt))
+    } else if amt >= h.big_blind_amount {
+        format!("{}: posts big blind {}", n, amount(h, amt))
+    } else {
+        format!("{}: posts small blind {}", n, amount(h, amt))
+    }
+}
+
+// iPoker splits a returning player's small & big blind into separate
+// "Post Dead" actions, or records the small blind as their own "Post SB" and
+// the big blind as dead. pokerstars prints them as a single post
+pub fn merge_dead_posts(h: &OhhHand, actions: &[Action]) -> Vec<Action> {
+    let mut merged: Vec<Action> = Vec::with_capacity(actions.len());
+
+    for a in actions {
+        if a.action == "Post Dead" {
+            let amt = a.amount.unwrap_or_default();
+            let same = |b: &&mut Action| b.player_id == a.player_id;
+            let earlier = match merged.last_mut().filter(same) {
+                Some(prev) if prev.action == "Post Dead" => Some(prev),
+                _ if amt >= h.big_blind_amount => merged
+                    .iter_mut()
+                    .filter(|b| b.action == "Post SB")
+                    .find(|b| b.player_id == a.player_id),
+                _ => None,
+            };
+            if let Some(prev) = earlier {
+                prev.action = a.action.clone();
+                prev.amount = Some(prev.amount.unwrap_or_default() + amt);
+                continue;
+            }
+        }
+        merged.push(a.clone());
+    }
+
+    merged
+}
+
+// a call never puts in more than it takes to match the bet, even where the
+// site counted part of a dead post as live, so the caller is not handed an
+// uncalled bet back
+fn cap_calls(h: &OhhHand, actions: &mut [Action]) {
+    let mut committed: HashMap<String, Money> = HashMap::new();
+    let mut bet = Money::ZERO;
+    for a in actions {
+        let (Some(pid), Some(amt)) = (&a.player_id, a.amount) else {
+            continue;
+        };
+        let c = committed.entry(pid.clone()).or_default();
+        let live = match a.action.as_str() {
+            "Call" if amt > bet - *c && (bet - *c).is_positive() => {
+                a.amount = Some(bet - *c);
+                bet - *c
+            }
+            action if is_post(action) => live_post_amount(h, action, amt),
+            _ => amt,
+        };
+        *c += live;
+        bet = bet.max(*c);
+    }
+}
+
+// the actions of a street as they are printed and validated: dead posts
+// merged, implied antes added and calls capped
+pub fn street_actions(h: &OhhHand, round: &Round) -> Vec<Action> {
+    let mut actions = merge_dead_posts(h, &round.actions);
+    if round.street == "Preflop" {
+        actions.splice(0..0, implied_ante_posts(h));
+    }
+    cap_calls(h, &mut actions);
+    actions
+}
+
+// some sites only record ante_amount on the hand and not the posts themselves
+pub fn implied_ante_posts(h: &OhhHand) -> Vec<Action> {
+    let ante = match h.ante_amount {
+        Some(a) if a.is_positive() => a,
+        _ => return Vec::new(),
+    };
+    let posted = h
+        .rounds
+        .iter()
+        .flat_map(|r| &r.actions)
+        .any(|a| a.action == "Post Ante");
+    if posted {
+        return Vec::new();
+    }
+
+    let mut players = h.players.clone();
+    players.sort_by_key(|p| p.seat);
+
+    players
+        .into_iter()
+        .map(|p| Action {
+            action_number: 0,
+            player_id: Some(p.id),
+            action: "Post Ante".to_string(),
+            amount: Some(ante.min(p.starting_stack)),
+            is_allin: Some(p.starting_stack <= ante),
+            cards: None,
+            extra: Extra::new(),
+        })
+        .collect()
+}
+
 pub fn act_line_with_context(
     h: &OhhHand,
     a: &Action,
-    tracker: &std::collections::HashMap<u32, (f64, f64, bool)>,
+    tracker: &HashMap<u32, (Money, Money, bool)>,
 ) -> Option<String> {
     let player_id = a.player_id.as_ref()?;
     let n = name_by_id(h, player_id);
     let act = &a.action;
-    let amt = a.amount.unwrap_or(0.0);
+    let amt = a.amount.unwrap_or_default();
     let allin = if a.is_allin.unwrap_or(false) {
         " and is all-in"
     } else {
@@ -368,24 +766,17 @@ pub fn act_line_with_context(
     };
 
     match act.as_str() {
-        "Post SB" => Some(format!("{}: posts small blind {}", n, fmt_money(amt))),
-        "Post BB" => Some(format!("{}: posts big blind {}", n, fmt_money(amt))),
-        "Post Ante" => Some(format!("{}: posts the ante {}", n, fmt_money(amt))),
+        "Post SB" => Some(format!("{}: posts small blind {}", n, amount(h, amt))),
+        "Post BB" => Some(format!("{}: posts big blind {}", n, amount(h, amt))),
+        "Post Ante" => Some(format!("{}: posts the ante {}", n, amount(h, amt))),
+        "Post Dead" | "Post Extra Blind" => Some(post_blinds_line(h, &n, amt)),
+        "Straddle" => Some(format!("{}: posts straddle {}", n, amount(h, amt))),
         "Dealt Cards" => {
-            let should_show = match &h.hero_player_id {
-                Some(hero_id) => hero_id == player_id,
-                None => true,
-            };
-
-            if should_show {
+            // everyone else's known cards are revealed at showdown instead
+            if h.hero_player_id.as_deref() == Some(player_id.as_str()) {
                 if let Some(card_list) = &a.cards {
                     if card_list.len() >= 2 {
-                        return Some(format!(
-                            "Dealt to {} [{} {}]",
-                            n,
-                            card(&card_list[0]),
-                            card(&card_list[1])
-                        ));
+                        return Some(format!("Dealt to {} [{}]", n, cards(card_list)));
                     }
                 }
             }
@@ -393,46 +784,45 @@ pub fn act_line_with_context(
         }
         "Fold" => Some(format!("{}: folds", n)),
         "Check" => Some(format!("{}: checks", n)),
-        "Call" => Some(format!("{}: calls {}{}", n, fmt_money(amt), allin)),
+        "Call" => Some(format!("{}: calls {}{}", n, amount(h, amt), allin)),
         "Bet" => {
-            if let Some((prev_bet, _total, has_bet)) = tracker.get(&a.action_number) {
-                if *has_bet && *prev_bet > 0.0 {
-                    let raise_amount = amt - prev_bet;
+            if let Some((prev_bet, total, has_bet)) = tracker.get(&a.action_number) {
+                if *has_bet && prev_bet.is_positive() {
+                    let raise_amount = *total - *prev_bet;
                     return Some(format!(
                         "{}: raises {} to {}{}",
                         n,
-                        fmt_money(raise_amount),
-                        fmt_money(amt),
+                        amount(h, raise_amount),
+                        amount(h, *total),
                         allin
                     ));
                 }
             }
-            Some(format!("{}: bets {}{}", n, fmt_money(amt), allin))
+            Some(format!("{}: bets {}{}", n, amount(h, amt), allin))
         }
         "Raise" => {
-            if let Some((prev_bet, _total, _has_bet)) = tracker.get(&a.action_number) {
-                if *prev_bet > 0.0 {
-                    let raise_amount = amt - prev_bet;
+            if let Some((prev_bet, total, _has_bet)) = tracker.get(&a.action_number) {
+                if prev_bet.is_positive() {
+                    let raise_amount = *total - *prev_bet;
                     return Some(format!(
                         "{}: raises {} to {}{}",
                         n,
-                        fmt_money(raise_amount),
-                        fmt_money(amt),
+                        amount(h, raise_amount),
+                        amount(h, *total),
                         allin
                     ));
                 }
             }
-            Some(format!("{}: bets {}{}", n, fmt_money(amt), allin))
+            Some(format!("{}: bets {}{}", n, amount(h, amt), allin))
         }
         "Shows Cards" => {
             if let Some(card_list) = &a.cards {
                 if card_list.len() >= 2 {
-                    return Some(format!(
-                        "{}: shows [{} {}]",
-                        n,
-                        card(&card_list[0]),
-                        card(&card_list[1])
-                    ));
+                    let board: Vec<String> = board_cards(h).into_iter().take(5).collect();
+                    let desc = describe_hand(h, card_list, &board)
+                        .map(|d| format!(" ({})", d))
+                        .unwrap_or_default();
+                    return Some(format!("{}: shows [{}]{}", n, cards(card_list), desc));
                 }
             }
             Some(format!("{}: shows", n))
@@ -442,56 +832,276 @@ pub fn act_line_with_context(
     }
 }
 
-pub fn summarize(h: &OhhHand) -> String {
-    if h.pots.is_empty() {
-        return "*** SUMMARY ***\nTotal pot $0.00 | Rake $0.00".to_string();
+pub fn sorted_pots(h: &OhhHand) -> Vec<&Pot> {
+    let mut pots: Vec<&Pot> = h.pots.iter().collect();
+    pots.sort_by_key(|p| p.number);
+    pots
+}
+
+pub fn pot_label(h: &OhhHand, idx: usize) -> String {
+    match (h.pots.len(), idx) {
+        (0..=1, _) => "pot".to_string(),
+        (_, 0) => "main pot".to_string(),
+        (2, _) => "side pot".to_string(),
+        (_, i) => format!("side pot-{}", i),
     }
+}
 
-    let pot = &h.pots[0];
-    let rake = pot.rake;
-    let total = pot.amount;
+pub fn collect_lines(h: &OhhHand) -> Vec<String> {
+    let mut lines = Vec::new();
 
+    // pokerstars lists the side pots first, working back to the main pot
+    for (idx, pot) in sorted_pots(h).iter().enumerate().rev() {
+        let label = pot_label(h, idx);
+        for w in &pot.player_wins {
+            lines.push(format!(
+                "{} collected {} from {}",
+                name_by_id(h, &w.player_id),
+                amount(h, w.win_amount),
+                label
+            ));
+        }
+    }
+
+    lines
+}
+
+pub fn total_pot_line(h: &OhhHand) -> String {
+    let pots = sorted_pots(h);
+    let total: Money = pots.iter().map(|p| p.amount).sum();
+    let rake: Money = pots.iter().map(|p| p.rake).sum();
+
+    let mut line = format!("Total pot {}", amount(h, total));
+    if pots.len() > 1 {
+        for (idx, pot) in pots.iter().enumerate() {
+            let label = pot_label(h, idx);
+            let mut chars = label.chars();
+            let label = match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => label,
+            };
+            line.push_str(&format!(" {} {}.", label, amount(h, pot.amount - pot.rake)));
+        }
+    }
+    line.push_str(&format!(" | Rake {}", amount(h, rake)));
+    line
+}
+
+pub fn board_cards(h: &OhhHand) -> Vec<String> {
     let mut board = Vec::new();
     for r in &h.rounds {
         if matches!(r.street.as_str(), "Flop" | "Turn" | "River") {
             board.extend(r.cards.clone());
         }
     }
+    board
+}
+
+// as printed, so a small blind merged into a dead post no longer counts
+fn posted_by(h: &OhhHand, pid: &str, action: &str) -> bool {
+    h.rounds.iter().flat_map(|r| street_actions(h, r)).any(|a| {
+        a.action == action && a.player_id.as_deref() == Some(pid)
+    })
+}
 
-    let mut lines = vec!["*** SUMMARY ***".to_string()];
-    lines.push(format!(
-        "Total pot {} | Rake {}",
-        fmt_money(total),
-        fmt_money(rake)
-    ));
+pub fn seat_summary_line(h: &OhhHand, p: &Player, board: &[String]) -> String {
+    let mut line = format!("Seat {}: {}", p.seat, p.name);
 
-    if !board.is_empty() {
-        lines.push(format!("Board [{}]", cards(&board)));
+    if p.seat == h.dealer_seat {
+        line.push_str(" (button)");
+    }
+    if posted_by(h, &p.id, "Post SB") {
+        line.push_str(" (small blind)");
+    } else if posted_by(h, &p.id, "Post BB") {
+        line.push_str(" (big blind)");
     }
 
-    for w in &pot.player_wins {
-        let seat = seat_by_id(h, &w.player_id);
-        let name = name_by_id(h, &w.player_id);
-        lines.push(format!(
-            "Seat {}: {} collected ({})",
-            seat,
-            name,
-            fmt_money(w.win_amount)
-        ));
+    let mut folded_on = None;
+    let mut shown = None;
+    let mut mucked = None;
+    let mut put_in = Money::ZERO;
+
+    for round in &h.rounds {
+        for a in &round.actions {
+            if a.player_id.as_deref() != Some(p.id.as_str()) {
+                continue;
+            }
+            match a.action.as_str() {
+                "Fold" if folded_on.is_none() => folded_on = Some(round.street.as_str()),
+                "Shows Cards" => shown = a.cards.clone(),
+                "Muck" => mucked = Some(a.cards.clone().unwrap_or_default()),
+                "Post Ante" => {}
+                _ => put_in += a.amount.unwrap_or_default(),
+            }
+        }
+    }
+
+    let won: Money = h
+        .pots
+        .iter()
+        .flat_map(|pot| &pot.player_wins)
+        .filter(|w| w.player_id == p.id)
+        .map(|w| w.win_amount)
+        .sum();
+    let first_board: Vec<String> = board.iter().take(5).cloned().collect();
+    // cards shown after folding, or to take a pot nobody contested, are not
+    // part of a showdown
+    let at_showdown = showdown_players(h).contains(&p.id.as_str());
+
+    if let Some(street) = folded_on {
+        match street {
+            "Preflop" if put_in.is_zero() => line.push_str(" folded before Flop (didn't bet)"),
+            "Preflop" => line.push_str(" folded before Flop"),
+            other => line.push_str(&format!(" folded on the {}", other)),
+        }
+    } else if let Some(hole) = shown.filter(|c| at_showdown && !c.is_empty()) {
+        let desc = describe_hand(h, &hole, &first_board)
+            .map(|d| format!(" with {}", d))
+            .unwrap_or_default();
+        if won.is_positive() {
+            line.push_str(&format!(
+                " showed [{}] and won ({}){}",
+                cards(&hole),
+                amount(h, won),
+                desc
+            ));
+        } else {
+            line.push_str(&format!(" showed [{}] and lost{}", cards(&hole), desc));
+        }
+    } else if won.is_positive() {
+        line.push_str(&format!(" collected ({})", amount(h, won)));
+    } else {
+        match mucked.filter(|c| !c.is_empty()) {
+            Some(hole) => line.push_str(&format!(" mucked [{}]", cards(&hole))),
+            None => line.push_str(" mucked"),
+        }
     }
 
-    lines.join("\n")
+    line
+}
+
+pub fn summarize(h: &OhhHand) -> String {
+    PokerStarsWriter.summary(h)
+}
+
+pub fn uncalled_bet(committed: &HashMap<String, Money>) -> Option<(String, Money)> {
+    let mut ranked: Vec<(&String, Money)> = committed.iter().map(|(k, v)| (k, *v)).collect();
+    ranked.sort_by_key(|r| std::cmp::Reverse(r.1));
+
+    let (pid, top) = ranked.first()?;
+    let next = ranked.get(1).map(|(_, v)| *v).unwrap_or_default();
+    let returned = *top - next;
+
+    if returned.is_positive() {
+        Some(((*pid).clone(), returned))
+    } else {
+        None
+    }
 }
 
 pub fn ohh_to_pokerstars_text(h: &OhhHand) -> String {
+    convert_hand(h).0
+}
+
+// the players who never folded, in the order they first acted, or nobody
+// when the hand ended without a showdown
+pub fn showdown_players(h: &OhhHand) -> Vec<&str> {
+    let actions = h.rounds.iter().flat_map(|r| &r.actions);
+    let folded: Vec<&str> = actions
+        .clone()
+        .filter(|a| a.action == "Fold")
+        .filter_map(|a| a.player_id.as_deref())
+        .collect();
+
+    let mut live: Vec<&str> = Vec::new();
+    for pid in actions.filter_map(|a| a.player_id.as_deref()) {
+        if !live.contains(&pid) && !folded.contains(&pid) {
+            live.push(pid);
+        }
+    }
+    if live.len() < 2 {
+        live.clear();
+    }
+    live
+}
+
+// known hole cards of players other than the hero who were still in the
+// hand at showdown and did not show or muck there themselves
+pub fn showdown_reveals(h: &OhhHand) -> Vec<Action> {
+    let actions: Vec<&Action> = h.rounds.iter().flat_map(|r| &r.actions).collect();
+    let acted = |pid: &str, action: &str| {
+        actions
+            .iter()
+            .any(|a| a.action == action && a.player_id.as_deref() == Some(pid))
+    };
+
+    let live = showdown_players(h);
+    if live.is_empty() {
+        return Vec::new();
+    }
+
+    let mut next = actions.iter().map(|a| a.action_number + 1).max().unwrap_or(0);
+    let mut reveals = Vec::new();
+    for a in &actions {
+        let Some(pid) = a.player_id.as_deref() else {
+            continue;
+        };
+        let known = a.action == "Dealt Cards" && a.cards.as_ref().is_some_and(|c| c.len() >= 2);
+        if !known
+            || h.hero_player_id.as_deref() == Some(pid)
+            || !live.contains(&pid)
+            || acted(pid, "Shows Cards")
+            || acted(pid, "Muck")
+        {
+            continue;
+        }
+        reveals.push(Action {
+            action_number: next,
+            player_id: Some(pid.to_string()),
+            action: "Shows Cards".to_string(),
+            amount: None,
+            is_allin: None,
+            cards: a.cards.clone(),
+            extra: Extra::new(),
+        });
+        next += 1;
+    }
+    reveals
+}
+
+pub fn convert_hand(h: &OhhHand) -> (String, Vec<Diagnostic>) {
+    write_hand(h, &PokerStarsWriter)
+}
+
+pub fn write_hand(h: &OhhHand, w: &dyn HandHistoryWriter) -> (String, Vec<Diagnostic>) {
+    let reveals = showdown_reveals(h);
+    let revealed;
+    let h = if reveals.is_empty() {
+        h
+    } else {
+        let mut copy = h.clone();
+        if let Some(last) = copy.rounds.last_mut() {
+            last.actions.extend(reveals);
+        }
+        revealed = copy;
+        &revealed
+    };
+
     let mut lines = Vec::new();
+    let mut diagnostics = Vec::new();
 
-    lines.push(build_header(h));
-    lines.push(build_table(h));
-    lines.push(build_seats(h));
+    lines.push(w.header(h));
+    let table = w.table(h);
+    if !table.is_empty() {
+        lines.push(table);
+    }
+    lines.push(w.seats(h));
 
     let mut all_board = Vec::new();
-    let mut street_pot_tracker = std::collections::HashMap::new();
+    let mut street_pot_tracker = HashMap::new();
+    let mut showdown_lines = Vec::new();
+    // cards shown when the hand ends on a fold stay on their street
+    let showdown = !showdown_players(h).is_empty();
 
     for round in &h.rounds {
         let street = &round.street;
@@ -499,47 +1109,89 @@ pub fn ohh_to_pokerstars_text(h: &OhhHand) -> String {
 
         all_board.extend(brd.clone());
 
-        let mut last_bet_amount: f64 = 0.0;
+        let mut last_bet_amount = Money::ZERO;
         let mut has_bet_this_street = false;
         let is_preflop = street == "Preflop";
+        let mut committed: HashMap<String, Money> = HashMap::new();
 
+        let mut ante_lines = Vec::new();
         let mut blind_lines = Vec::new();
         let mut dealt_lines = Vec::new();
         let mut other_lines = Vec::new();
+        let mut shown_lines = Vec::new();
+
+        let actions = street_actions(h, round);
+
+        for action in &actions {
+            if !is_known_action(&action.action) {
+                warn!(
+                    "hand {}: unknown action {} at {}",
+                    h.game_number, action.action, action.action_number
+                );
+                diagnostics.push(Diagnostic::UnknownAction {
+                    game_number: h.game_number.clone(),
+                    action_number: action.action_number,
+                    action: action.action.clone(),
+                });
+            }
 
-        for action in &round.actions {
             if let Some(amt) = action.amount {
+                let live = if is_post(&action.action) {
+                    live_post_amount(h, &action.action, amt)
+                } else {
+                    amt
+                };
+                let player_committed = match &action.player_id {
+                    Some(pid) if live.is_positive() => {
+                        let c = committed.entry(pid.clone()).or_default();
+                        *c += live;
+                        *c
+                    }
+                    _ => live,
+                };
+
                 match action.action.as_str() {
-                    "Post SB" | "Post BB" | "Post Ante" => {
-                        last_bet_amount = amt;
+                    a if is_post(a) => {
+                        last_bet_amount = last_bet_amount.max(player_committed);
                     }
                     "Bet" | "Raise" => {
                         let prev_bet = last_bet_amount;
-                        last_bet_amount = amt;
+                        last_bet_amount = player_committed;
 
-                        street_pot_tracker
-                            .insert(action.action_number, (prev_bet, amt, has_bet_this_street));
+                        street_pot_tracker.insert(
+                            action.action_number,
+                            (prev_bet, player_committed, has_bet_this_street),
+                        );
                         has_bet_this_street = true;
                     }
                     _ => {}
                 }
             }
 
-            if let Some(line) = act_line_with_context(h, action, &street_pot_tracker) {
+            if let Some(line) = w.action_line(h, action, &street_pot_tracker) {
                 match action.action.as_str() {
-                    "Post SB" | "Post BB" | "Post Ante" => blind_lines.push(line),
+                    "Post Ante" => ante_lines.push(line),
+                    a if is_post(a) => blind_lines.push(line),
                     "Dealt Cards" => dealt_lines.push(line),
+                    "Shows Cards" | "Muck" if showdown => showdown_lines.push(line),
+                    "Shows Cards" | "Muck" => shown_lines.push(line),
+                    _ if street == "Showdown" => showdown_lines.push(line),
                     _ => other_lines.push(line),
                 }
             }
         }
 
         if is_preflop {
+            // pokerstars lists every ante before the blinds
+            for line in ante_lines {
+                lines.push(line);
+            }
             for line in blind_lines {
                 lines.push(line);
             }
+            lines.extend(w.before_hole_cards(h));
 
-            let header = street_header(street, brd, &all_board);
+            let header = w.street_header(street, brd, &all_board);
             if !header.is_empty() {
                 lines.push(header);
             }
@@ -552,11 +1204,14 @@ pub fn ohh_to_pokerstars_text(h: &OhhHand) -> String {
                 lines.push(line);
             }
         } else {
-            let header = street_header(street, brd, &all_board);
-            if !header.is_empty() {
+            let header = w.street_header(street, brd, &all_board);
+            if !header.is_empty() && street != "Showdown" {
                 lines.push(header);
             }
 
+            for line in ante_lines {
+                lines.push(line);
+            }
             for line in blind_lines {
                 lines.push(line);
             }
@@ -567,28 +1222,431 @@ pub fn ohh_to_pokerstars_text(h: &OhhHand) -> String {
                 lines.push(line);
             }
         }
+
+        if let Some((pid, returned)) = uncalled_bet(&committed) {
+            lines.push(w.uncalled_line(h, &pid, returned));
+        }
+        lines.extend(shown_lines);
+    }
+
+    if !showdown_lines.is_empty() {
+        lines.push(w.street_header("Showdown", &[], &all_board));
+        lines.extend(showdown_lines);
+    }
+
+    lines.extend(w.collect_lines(h));
+    lines.push(w.summary(h));
+
+    (lines.join("\n"), diagnostics)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConvertOptions {
+    // refuse hands that fail validation instead of converting them with warnings
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub dialect: Dialect,
+    // player id, name or display name to treat as the hero, or "auto" to
+    // pick the player seated in the most hands
+    #[serde(default)]
+    pub hero: Option<String>,
+    // replace player and table names with pseudonyms
+    #[serde(default)]
+    pub anonymize: Option<AnonymizeOptions>,
+    // which of a player's names to print
+    #[serde(default)]
+    pub names: NamePolicy,
+    // names to print instead, ahead of `names`
+    #[serde(default)]
+    pub aliases: Aliases,
+    // only convert hands matching this filter expression, see `filter`
+    #[serde(default)]
+    pub filter: Option<String>,
+    // also cut the output into parts, see `ConvertReport::parts`
+    #[serde(default)]
+    pub split: Option<SplitOptions>,
+}
+
+pub const AUTO_HERO: &str = "auto";
+
+// false when nobody in the hand matches
+pub fn set_hero(h: &mut OhhHand, hero: &str) -> bool {
+    let found = h
+        .players
+        .iter()
+        .find(|p| p.id == hero || p.name == hero || p.display.as_deref() == Some(hero));
+    match found {
+        Some(p) => {
+            h.hero_player_id = Some(p.id.clone());
+            true
+        }
+        None => false,
+    }
+}
+
+// the name of the player seated in the most hands, as long as that is at
+// least half of them and nobody else is seated as often
+pub fn detect_hero<'a, I>(hands: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a OhhHand>,
+{
+    let mut seated: HashMap<&str, usize> = HashMap::new();
+    let mut total = 0;
+    for h in hands {
+        total += 1;
+        for p in &h.players {
+            *seated.entry(p.name.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(&str, usize)> = seated.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    match counts.as_slice() {
+        [(name, n), rest @ ..]
+            if n * 2 >= total && rest.first().is_none_or(|(_, m)| m < n) =>
+        {
+            Some(name.to_string())
+        }
+        _ => None,
+    }
+}
+
+pub fn convert_ohh_file(content: &str) -> Result<ConvertReport, ConvertError> {
+    convert_ohh_file_with(content, &ConvertOptions::default())
+}
+
+pub fn convert_ohh_file_with(
+    content: &str,
+    options: &ConvertOptions,
+) -> Result<ConvertReport, ConvertError> {
+    debug!(
+        "convert_ohh_file called with {} bytes, strict: {}, dialect: {}",
+        content.len(),
+        options.strict,
+        options.dialect.name()
+    );
+
+    let (hands, diagnostics) = parse_ohh_chunks(content)?;
+    convert_hands(hands, diagnostics, options)
+}
+
+// converts already parsed hands; `diagnostics` carries anything reported
+// while reading them
+pub fn convert_hands(
+    mut hands: Vec<OhhHand>,
+    mut diagnostics: Vec<Diagnostic>,
+    options: &ConvertOptions,
+) -> Result<ConvertReport, ConvertError> {
+    diagnostics.extend(prepare_hands(&mut hands, options)?);
+    let anonymizer = options
+        .anonymize
+        .as_ref()
+        .map(|anonymize| Anonymizer::new(anonymize, &hands));
+    convert_prepared_hands(hands, diagnostics, options, anonymizer.as_ref())
+}
+
+// sets the hero asked for in `options` on every hand
+pub fn select_hero(hands: &mut [OhhHand], options: &ConvertOptions) -> Vec<Diagnostic> {
+    let hero = resolve_hero(hands.iter(), options);
+    set_heroes(hands, hero.as_deref())
+}
+
+// the hero asked for in `options`, with "auto" detected from all of `hands`
+pub fn resolve_hero<'a, I>(hands: I, options: &ConvertOptions) -> Option<String>
+where
+    I: IntoIterator<Item = &'a OhhHand>,
+{
+    match options.hero.as_deref() {
+        Some(hero) if hero.eq_ignore_ascii_case(AUTO_HERO) => {
+            let found = detect_hero(hands);
+            match &found {
+                Some(name) => info!("detected hero {}", name),
+                None => warn!("could not detect a hero, hole cards only show at showdown"),
+            }
+            found
+        }
+        other => other.map(str::to_string),
     }
+}
 
-    lines.push(summarize(h));
+pub fn set_heroes(hands: &mut [OhhHand], hero: Option<&str>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if let Some(hero) = hero {
+        for hand in hands.iter_mut() {
+            if !set_hero(hand, hero) {
+                // nobody else's perspective either
+                hand.hero_player_id = None;
+                diagnostics.push(Diagnostic::HeroNotSeated {
+                    game_number: hand.game_number.clone(),
+                    hero: hero.to_string(),
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+pub fn parse_filter(options: &ConvertOptions) -> Result<Option<Filter>, ConvertError> {
+    match options.filter.as_deref().map(str::trim) {
+        Some(text) if !text.is_empty() => Filter::parse(text)
+            .map(Some)
+            .map_err(|e| ConvertError::Input(format!("invalid filter: {}", e))),
+        _ => Ok(None),
+    }
+}
 
-    lines.join("\n")
+// merges repeated hands, selects the hero, then drops the hands the filter
+// does not match. the hero comes first so it is detected from every hand and
+// filters can ask about it
+pub fn prepare_hands(
+    hands: &mut Vec<OhhHand>,
+    options: &ConvertOptions,
+) -> Result<Vec<Diagnostic>, ConvertError> {
+    let mut diagnostics = Vec::new();
+    let counts = prepare_inputs(&mut [(hands, &mut diagnostics)], options)?;
+    if counts[0].total > 0 && counts[0].matched == 0 {
+        return Err(ConvertError::NothingMatched);
+    }
+    Ok(diagnostics)
 }
 
-pub fn convert_ohh_file(content: &str) -> Result<String, String> {
-    debug!("convert_ohh_file called with {} bytes", content.len());
+// `prepare_hands` for several inputs at once: a hand in more than one of them
+// is kept in the first, "auto" picks one hero from all of them and the filter
+// applies to each. `diagnostics` gets what is found in that input, and the
+// counts say how many of its hands were left after merging and how many of
+// those match
+pub fn prepare_inputs(
+    inputs: &mut [(&mut Vec<OhhHand>, &mut Vec<Diagnostic>)],
+    options: &ConvertOptions,
+) -> Result<Vec<FilterCount>, ConvertError> {
+    let filter = parse_filter(options)?;
+    let mut groups: Vec<&mut Vec<OhhHand>> =
+        inputs.iter_mut().map(|(hands, _)| &mut **hands).collect();
+    for (g, diagnostic) in dedupe_groups(&mut groups) {
+        inputs[g].1.push(diagnostic);
+    }
 
-    let hands = parse_ohh_chunks(content)
-        .map_err(|e| format!("failed to parse ohh content: {}", e))?;
+    let hero = resolve_hero(inputs.iter().flat_map(|(hands, _)| hands.iter()), options);
+    let mut counts = Vec::with_capacity(inputs.len());
+    for (hands, diagnostics) in inputs.iter_mut() {
+        diagnostics.extend(set_heroes(hands, hero.as_deref()));
+        let total = hands.len();
+        if let Some(filter) = &filter {
+            hands.retain(|h| filter.matches(h));
+            diagnostics.retain(|d| {
+                d.game_number()
+                    .is_none_or(|game| hands.iter().any(|h| h.game_number == game))
+            });
+        }
+        counts.push(FilterCount {
+            total,
+            matched: hands.len(),
+        });
+    }
+    if filter.is_some() {
+        let total: usize = counts.iter().map(|c| c.total).sum();
+        let matched: usize = counts.iter().map(|c| c.matched).sum();
+        info!("{} of {} hands match the filter", matched, total);
+    }
+    Ok(counts)
+}
 
-    debug!("converting {} hands to pokerstars format", hands.len());
-    let converted_hands: Vec<String> = hands.iter().map(ohh_to_pokerstars_text).collect();
-    let result = converted_hands.join("\n\n\n\n");
+// how many hands the filter in `options` keeps, with the hero selected as for
+// a conversion
+pub fn count_matches(
+    mut hands: Vec<OhhHand>,
+    options: &ConvertOptions,
+) -> Result<FilterCount, ConvertError> {
+    let filter = parse_filter(options)?;
+    dedupe_hands(&mut hands);
+    select_hero(&mut hands, options);
+    let matched = match filter {
+        Some(filter) => hands.iter().filter(|h| filter.matches(h)).count(),
+        None => hands.len(),
+    };
+    Ok(FilterCount {
+        total: hands.len(),
+        matched,
+    })
+}
+
+// converts hands whose hero is already selected. batches share one
+// `anonymizer` so pseudonyms agree across files
+pub fn convert_prepared_hands(
+    mut hands: Vec<OhhHand>,
+    mut diagnostics: Vec<Diagnostic>,
+    options: &ConvertOptions,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<ConvertReport, ConvertError> {
+    let writer = options.dialect.writer();
+    let separator = writer.separator();
+    debug!("converting {} hands to {} format", hands.len(), options.dialect.name());
+    let mut converted_hands = Vec::with_capacity(hands.len());
+    let mut game_numbers = Vec::with_capacity(hands.len());
+    let mut spans = Vec::with_capacity(hands.len());
+    let mut offset = 0;
+    // names are chosen once for the whole input so clashes are settled the
+    // same way in every hand; pseudonyms replace any naming choice
+    let printed = match anonymizer {
+        Some(anonymizer) => {
+            hands.iter_mut().for_each(|h| anonymizer.apply(h));
+            PrintedNames::new(&hands, NamePolicy::Name, &Aliases::new())
+        }
+        None => PrintedNames::new(&hands, options.names, &options.aliases),
+    };
+    for mut hand in hands {
+        printed.apply(&mut hand);
+
+        let issues = validate(&hand);
+        for issue in &issues {
+            warn!("hand {}: {}", hand.game_number, issue);
+        }
+
+        if options.strict && !issues.is_empty() {
+            diagnostics.push(Diagnostic::RejectedHand {
+                game_number: hand.game_number.clone(),
+                issues,
+            });
+            continue;
+        }
+        // amounts that do not fit cannot be printed, only reported
+        let overflowed = issues.contains(&Issue::Overflow);
+        diagnostics.extend(
+            issues
+                .into_iter()
+                .map(|issue| Diagnostic::from_issue(&hand.game_number, issue)),
+        );
+        if overflowed {
+            continue;
+        }
+
+        let (text, hand_diagnostics) = write_hand(&hand, writer);
+        let start = if converted_hands.is_empty() { 0 } else { offset + separator.len() };
+        offset = start + text.len();
+        spans.push(HandSpan::new(&hand, start, offset));
+        converted_hands.push(text);
+        game_numbers.push(hand.game_number.clone());
+        diagnostics.extend(hand_diagnostics);
+    }
+
+    if converted_hands.is_empty() {
+        return Err(ConvertError::Rejected(diagnostics));
+    }
+
+    let result = converted_hands.join(separator);
 
     if result.trim().is_empty() {
-        return Err("conversion produced no output. check file formatting.".to_string());
+        return Err(ConvertError::EmptyOutput);
+    }
+
+    if !diagnostics.is_empty() {
+        info!("converted {} hands with {} warnings", game_numbers.len(), diagnostics.len());
+    }
+
+    let report = ConvertReport::new(result, game_numbers, diagnostics);
+    Ok(report.with_spans(spans, separator, options.split.as_ref()))
+}
+
+// canonical ohh for re-export: numeric player ids, players in seat order and
+// rounds, actions and pots sorted by their numbers
+pub fn normalize_hand(h: &OhhHand) -> OhhHand {
+    let mut h = h.clone();
+
+    if h.spec_version.is_none() {
+        h.spec_version = Some(OHH_SPEC_VERSION.to_string());
+    }
+    h.tournament = is_tournament(&h);
+    h.players.sort_by_key(|p| p.seat);
+
+    if h.players.iter().any(|p| numeric_player_id(&p.id).is_none()) {
+        let ids: HashMap<String, String> = h
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.id.clone(), (i + 1).to_string()))
+            .collect();
+        let renumber = |id: &mut String| {
+            if let Some(n) = ids.get(id.as_str()) {
+                *id = n.clone();
+            }
+        };
+
+        for p in &mut h.players {
+            renumber(&mut p.id);
+        }
+        if let Some(id) = h.hero_player_id.as_mut() {
+            renumber(id);
+        }
+        for a in h.rounds.iter_mut().flat_map(|r| &mut r.actions) {
+            if let Some(id) = a.player_id.as_mut() {
+                renumber(id);
+            }
+        }
+        for w in h.pots.iter_mut().flat_map(|p| &mut p.player_wins) {
+            renumber(&mut w.player_id);
+        }
     }
 
-    Ok(result)
+    h.rounds.sort_by_key(|r| r.id);
+    for r in &mut h.rounds {
+        r.actions.sort_by_key(|a| a.action_number);
+    }
+    h.pots.sort_by_key(|p| p.number);
+
+    h
+}
+
+pub fn ohh_json(h: OhhHand) -> Result<String, ConvertError> {
+    serde_json::to_string(&OhhFile { ohh: h })
+        .map_err(|e| ConvertError::Input(format!("failed to write ohh: {}", e)))
+}
+
+// re-exports every readable hand as canonical ohh, one json object per line
+pub fn normalize_ohh_file(content: &str) -> Result<ConvertReport, ConvertError> {
+    normalize_ohh_file_with(content, &ConvertOptions::default())
+}
+
+// only the hero, filter and anonymize options apply to ohh output
+pub fn normalize_ohh_file_with(
+    content: &str,
+    options: &ConvertOptions,
+) -> Result<ConvertReport, ConvertError> {
+    debug!("normalize_ohh_file called with {} bytes", content.len());
+
+    let (mut hands, mut diagnostics) = parse_ohh_chunks(content)?;
+    diagnostics.extend(prepare_hands(&mut hands, options)?);
+    let anonymizer = options
+        .anonymize
+        .as_ref()
+        .map(|anonymize| Anonymizer::new(anonymize, &hands));
+    normalize_prepared_hands(hands, diagnostics, options, anonymizer.as_ref())
+}
+
+// one normalized ohh object per line for hands whose hero is already selected
+pub fn normalize_prepared_hands(
+    hands: Vec<OhhHand>,
+    diagnostics: Vec<Diagnostic>,
+    options: &ConvertOptions,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<ConvertReport, ConvertError> {
+    let mut output = String::new();
+    let mut game_numbers = Vec::with_capacity(hands.len());
+    let mut spans = Vec::with_capacity(hands.len());
+    for mut hand in hands {
+        if let Some(anonymizer) = anonymizer {
+            anonymizer.apply(&mut hand);
+        }
+        let start = output.len();
+        output.push_str(&ohh_json(normalize_hand(&hand))?);
+        output.push('\n');
+        spans.push(HandSpan::new(&hand, start, output.len()));
+        game_numbers.push(hand.game_number.clone());
+    }
+
+    info!("normalized {} hands", game_numbers.len());
+    let report = ConvertReport::new(output, game_numbers, diagnostics);
+    Ok(report.with_spans(spans, "", options.split.as_ref()))
 }
 
 #[cfg(test)]
@@ -601,6 +1659,15 @@ mod tests {
         assert_eq!(fmt_money(1000.0), "$1,000.00");
         assert_eq!(fmt_money(0.05), "$0.05");
         assert_eq!(fmt_money(-5.0), "-$5.00");
+
+        // rounding happens once on the whole amount, not per component
+        assert_eq!(fmt_money(0.995), "$1.00");
+        assert_eq!(fmt_money(999.995), "$1,000.00");
+        assert_eq!(fmt_money(0.005), "$0.01");
+        assert_eq!(fmt_money(-0.004), "$0.00");
+        assert_eq!(fmt_money(-0.005), "-$0.01");
+        assert_eq!(fmt_money(0.1 + 0.2), "$0.30");
+        assert_eq!(fmt_money(1234567.891), "$1,234,567.89");
     }
 
     #[test]
@@ -618,12 +1685,12 @@ mod tests {
 
     #[test]
     fn test_hand_conversion_with_dealt_cards() {
-        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"test123","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"TestTable","table_size":6,"dealer_seat":3,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"PPC","players":[{"id":1,"seat":1,"name":"Player1","starting_stack":10.0},{"id":2,"seat":2,"name":"Player2","starting_stack":10.0}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Dealt Cards","cards":["As","Kd"]},{"action_number":1,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":2,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":3,"player_id":1,"action":"Raise","amount":0.3},{"action_number":4,"player_id":2,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.2,"rake":0,"player_wins":[{"player_id":1,"win_amount":0.2}]}]}}"#;
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"test123","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"TestTable","table_size":6,"dealer_seat":3,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"PPC","hero_player_id":1,"players":[{"id":1,"seat":1,"name":"Player1","starting_stack":10.0},{"id":2,"seat":2,"name":"Player2","starting_stack":10.0}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Dealt Cards","cards":["As","Kd"]},{"action_number":1,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":2,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":3,"player_id":1,"action":"Raise","amount":0.3},{"action_number":4,"player_id":2,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.2,"rake":0,"player_wins":[{"player_id":1,"win_amount":0.2}]}]}}"#;
 
         let result = convert_ohh_file(input);
         assert!(result.is_ok(), "Conversion should succeed");
 
-        let output = result.unwrap();
+        let output = result.unwrap().output;
         println!("\n=== TEST OUTPUT ===\n{}\n=== END ===\n", output);
 
         // Verify key components
@@ -632,11 +1699,11 @@ mod tests {
             "Should have header"
         );
         assert!(
-            output.contains("Player1: posts small blind $0.05"),
+            output.contains("Player1: posts small blind 0.05"),
             "Should have SB"
         );
         assert!(
-            output.contains("Player2: posts big blind $0.10"),
+            output.contains("Player2: posts big blind 0.10"),
             "Should have BB"
         );
         assert!(
@@ -662,7 +1729,7 @@ mod tests {
         let result = convert_ohh_file(input);
         assert!(result.is_ok(), "Conversion should succeed");
 
-        let output = result.unwrap();
+        let output = result.unwrap().output;
         println!("\n=== RAISE TEST OUTPUT ===\n{}\n=== END ===\n", output);
 
         // Check that raises are formatted correctly
@@ -680,17 +1747,17 @@ mod tests {
         let result = convert_ohh_file(input);
         assert!(result.is_ok(), "Real hand conversion should succeed");
 
-        let output = result.unwrap();
+        let output = result.unwrap().output;
         println!("\n=== REAL HAND OUTPUT ===\n{}\n=== END ===\n", output);
 
         // Verify the output has all expected components
         assert!(
-            output.contains("Dealt to DubNation"),
-            "Should show DubNation's cards"
+            output.contains("Hold'em No Limit (0.05/0.10 PPC)"),
+            "Play money should not get a dollar sign"
         );
         assert!(
-            output.contains("Dealt to -c6EEVvXCE"),
-            "Should show bdawg's cards"
+            !output.contains("Dealt to"),
+            "Observed hands have no hero to deal to"
         );
         assert!(
             output.contains("*** FLOP *** [4d 3c Kd]"),
@@ -706,6 +1773,506 @@ mod tests {
             output.contains("-c6EEVvXCE: shows [8s Ac]"),
             "Should show showdown cards"
         );
+
+        // Show/muck actions move out of the river into their own section
+        assert!(output.contains(
+            "-c6EEVvXCE: raises 1.02 to 1.50\nDubNation: calls 1.02\n*** SHOW DOWN ***\n\
+             DubNation: shows [Ks 2c] (a pair of Kings)\n\
+             -c6EEVvXCE: shows [8s Ac] (high card Ace)\n\
+             DubNation collected 3.97 from pot\n*** SUMMARY ***"
+        ));
+
+        // Every seat gets a summary line
+        assert!(output.contains("Seat 1: Agapito (small blind) folded before Flop\n"));
+        assert!(output.contains(
+            "Seat 4: DubNation (big blind) showed [Ks 2c] and won (3.97) with a pair of Kings"
+        ));
+        assert!(output.contains("Seat 5: CFFl2rCOze folded before Flop (didn't bet)"));
+        assert!(output.contains("Seat 6: -c6EEVvXCE showed [8s Ac] and lost with high card Ace"));
+        assert!(output.contains("Seat 8: JzhSREGpIj (button) folded before Flop (didn't bet)"));
+    }
+
+    #[test]
+    fn test_folded_players_did_not_show() {
+        // cut down from amitlngvtjjt in the sample file, where the folded
+        // players' cards are recorded as shown
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"amitlngvtjjt","start_date_utc":"2023-12-05T02:56:02.112Z","table_name":"TestTable","table_size":10,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Agapito","starting_stack":19.9},{"id":5,"seat":5,"name":"CFFl2rCOze","starting_stack":11.2},{"id":8,"seat":8,"name":"JzhSREGpIj","starting_stack":8.55},{"id":9,"seat":9,"name":"Aheadboat","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":5,"action":"Dealt Cards","cards":["2h","5s"]},{"action_number":1,"player_id":8,"action":"Dealt Cards","cards":["Ah","Ks"]},{"action_number":2,"player_id":8,"action":"Post SB","amount":0.05},{"action_number":3,"player_id":9,"action":"Post BB","amount":0.1},{"action_number":4,"player_id":1,"action":"Call","amount":0.1},{"action_number":5,"player_id":5,"action":"Fold"},{"action_number":6,"player_id":8,"action":"Raise","amount":0.25},{"action_number":7,"player_id":9,"action":"Fold"},{"action_number":8,"player_id":1,"action":"Call","amount":0.2}]},{"id":1,"street":"Flop","cards":["5d","6s","6d"],"actions":[{"action_number":0,"player_id":8,"action":"Check"},{"action_number":1,"player_id":1,"action":"Bet","amount":0.37},{"action_number":2,"player_id":8,"action":"Call","amount":0.37}]},{"id":2,"street":"Turn","cards":["9d"],"actions":[{"action_number":0,"player_id":8,"action":"Check"},{"action_number":1,"player_id":1,"action":"Bet","amount":9.03},{"action_number":2,"player_id":8,"action":"Fold"},{"action_number":3,"player_id":8,"action":"Shows Cards","cards":["Ah","Ks"]},{"action_number":4,"player_id":5,"action":"Shows Cards","cards":["2h","5s"]}]}],"pots":[{"number":0,"amount":1.44,"rake":0,"player_wins":[{"player_id":1,"win_amount":1.44}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== FOLDED SHOW OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.contains("Seat 1: Agapito (button) collected ($1.44)\n"));
+        assert!(output.contains("Seat 5: CFFl2rCOze folded before Flop (didn't bet)\n"));
+        assert!(output.contains("Seat 8: JzhSREGpIj (small blind) folded on the Turn\n"));
+        assert!(output.contains("Seat 9: Aheadboat (big blind) folded before Flop"));
+        assert!(!output.contains("showed"));
+        assert!(!output.contains("*** SHOW DOWN ***"));
+    }
+
+    #[test]
+    fn test_voluntary_show_after_fold() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"show1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"TestTable","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Ann","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Raise","amount":0.25},{"action_number":3,"player_id":2,"action":"Fold"},{"action_number":4,"player_id":1,"action":"Shows Cards","cards":["Ah","Kh"]}]}],"pots":[{"number":0,"amount":0.2,"rake":0,"player_wins":[{"player_id":1,"win_amount":0.2}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== VOLUNTARY SHOW OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.contains(
+            "Bob: folds\n\
+             Uncalled bet ($0.20) returned to Ann\n\
+             Ann: shows [Ah Kh] (high card Ace)\n\
+             Ann collected $0.20 from pot\n*** SUMMARY ***"
+        ));
+        assert!(!output.contains("*** SHOW DOWN ***"));
+        assert!(output.contains("Seat 1: Ann (button) (small blind) collected ($0.20)"));
+    }
+
+    #[test]
+    fn test_side_pots_summary() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"side_pots","start_date_utc":"2023-12-05T06:23:28.158Z","table_name":"TestTable","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Short","starting_stack":0.5},{"id":2,"seat":2,"name":"Middle","starting_stack":1.0},{"id":3,"seat":3,"name":"Deep","starting_stack":5.0}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":2,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":3,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Raise","amount":0.5,"is_allin":true},{"action_number":3,"player_id":2,"action":"Raise","amount":0.95,"is_allin":true},{"action_number":4,"player_id":3,"action":"Call","amount":0.9}]}],"pots":[{"number":0,"amount":1.5,"rake":0.05,"player_wins":[{"player_id":1,"win_amount":1.45}]},{"number":1,"amount":1.0,"rake":0,"player_wins":[{"player_id":3,"win_amount":1.0}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== SIDE POT OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.contains("Total pot $2.50 Main pot $1.45. Side pot $1.00. | Rake $0.05"));
+        assert!(output.contains("Deep collected $1.00 from side pot\nShort collected $1.45 from main pot"));
+        assert!(output.contains("Seat 1: Short (button) collected ($1.45)"));
+        assert!(output.contains("Seat 3: Deep (big blind) collected ($1.00)"));
+    }
+
+    #[test]
+    fn test_uncalled_bet_returned() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"uncalled","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"TestTable","table_size":10,"dealer_seat":7,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":4,"seat":4,"name":"DubNation","starting_stack":10},{"id":8,"seat":8,"name":"JzhSREGpIj","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":8,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":4,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":8,"action":"Call","amount":0.05},{"action_number":3,"player_id":4,"action":"Raise","amount":0.2},{"action_number":4,"player_id":8,"action":"Call","amount":0.2}]},{"id":1,"cards":["Ac","8s","9c"],"street":"Flop","actions":[{"action_number":0,"player_id":8,"action":"Check","amount":0},{"action_number":1,"player_id":4,"action":"Raise","amount":1.5},{"action_number":2,"player_id":8,"action":"Fold","amount":0}]}],"pots":[{"number":0,"amount":0.6,"rake":0,"player_wins":[{"player_id":4,"win_amount":0.6}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== UNCALLED OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.contains("DubNation: raises $0.20 to $0.30"));
+        assert!(output.contains("DubNation: bets $1.50"));
+        assert!(output.contains(
+            "JzhSREGpIj: folds\nUncalled bet ($1.50) returned to DubNation\nDubNation collected $0.60 from pot"
+        ));
+    }
+
+    #[test]
+    fn test_dead_blinds_and_straddle() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"romcftevli9g","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"TestTable","table_size":10,"dealer_seat":7,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Returning","starting_stack":10},{"id":4,"seat":4,"name":"Straddler","starting_stack":10},{"id":8,"seat":8,"name":"Small","starting_stack":10},{"id":9,"seat":9,"name":"Big","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":8,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":9,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Post Dead","amount":0.05},{"action_number":3,"player_id":1,"action":"Post Dead","amount":0.1},{"action_number":4,"player_id":4,"action":"Straddle","amount":0.2},{"action_number":5,"player_id":1,"action":"Call","amount":0.1},{"action_number":6,"player_id":8,"action":"Raise","amount":0.55},{"action_number":7,"player_id":9,"action":"Fold"},{"action_number":8,"player_id":1,"action":"Fold"},{"action_number":9,"player_id":4,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.75,"rake":0,"player_wins":[{"player_id":8,"win_amount":0.75}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== DEAD BLIND OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.contains(
+            "Small: posts small blind $0.05\n\
+             Big: posts big blind $0.10\n\
+             Returning: posts small & big blinds $0.15\n\
+             Straddler: posts straddle $0.20\n*** HOLE CARDS ***"
+        ));
+        assert!(output.contains("Small: raises $0.40 to $0.60"));
+        assert!(output.contains("Uncalled bet ($0.40) returned to Small"));
+    }
+
+    #[test]
+    fn test_dead_blind_poster_is_raised() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"dead2","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"TestTable","table_size":10,"dealer_seat":7,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Returning","starting_stack":10},{"id":8,"seat":8,"name":"Small","starting_stack":10},{"id":9,"seat":9,"name":"Big","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":8,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":9,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Post Dead","amount":0.05},{"action_number":3,"player_id":1,"action":"Post Dead","amount":0.1},{"action_number":4,"player_id":1,"action":"Check"},{"action_number":5,"player_id":8,"action":"Raise","amount":0.25},{"action_number":6,"player_id":9,"action":"Fold"},{"action_number":7,"player_id":1,"action":"Call","amount":0.2}]},{"id":1,"street":"Flop","cards":["2c","7d","9h"],"actions":[{"action_number":0,"player_id":8,"action":"Bet","amount":0.5},{"action_number":1,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.75,"rake":0,"player_wins":[{"player_id":8,"win_amount":0.75}]}]}}"#;
+
+        let report = convert_ohh_file(input).unwrap();
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+        // the big blind in the dead post is live, so calling the raise takes $0.20
+        assert!(report.output.contains("Small: raises $0.20 to $0.30"));
+        assert!(report.output.contains("Returning: calls $0.20"));
+        assert!(!report.output.contains("returned to Small\n*** FLOP"));
+        assert!(report.output.contains("Total pot $0.75 | Rake $0.00"));
+    }
+
+    #[test]
+    fn test_dead_big_blind_after_own_small_blind() {
+        // sample hands where iPoker records a returning player's small blind
+        // as their own post and only the big blind as dead
+        let n1 = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"n1lceyetjqya","start_date_utc":"2023-12-05T04:39:17.881Z","table_name":"pglCX2WsUJbPBjsNSE1siiDJy","table_size":10,"dealer_seat":8,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"PPC","players":[{"id":1,"seat":1,"name":"Agapito","starting_stack":22.3},{"id":4,"seat":4,"name":"DubNation","starting_stack":26.57},{"id":8,"seat":8,"name":"JzhSREGpIj","starting_stack":23.67}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":4,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Post Dead","amount":0.1},{"action_number":3,"player_id":8,"action":"Raise","amount":0.3},{"action_number":4,"player_id":1,"action":"Call","amount":0.2},{"action_number":5,"player_id":4,"action":"Call","amount":0.2}]},{"id":1,"cards":["2d","4s","Kh"],"street":"Flop","actions":[{"action_number":0,"player_id":1,"action":"Raise","amount":0.47},{"action_number":1,"player_id":4,"action":"Fold"},{"action_number":2,"player_id":8,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.95,"rake":0,"player_wins":[{"player_id":1,"win_amount":0.95}]}]}}"#;
+        let report = convert_ohh_file(n1).unwrap();
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+        assert!(report.output.contains(
+            "Agapito: posts small & big blinds 0.15\n\
+             DubNation: posts big blind 0.10\n*** HOLE CARDS ***"
+        ));
+        assert!(report.output.contains("Seat 1: Agapito collected (0.95)"));
+        assert!(report
+            .output
+            .contains("Agapito: calls 0.20\nDubNation: calls 0.20\n*** FLOP ***"));
+
+        // the call is recorded as if the whole dead post were live; it still
+        // only matches the raise instead of getting part of itself back
+        let amit = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"amitlngvtjjt","start_date_utc":"2023-12-05T03:13:10.367Z","table_name":"pglCX2WsUJbPBjsNSE1siiDJy","table_size":10,"dealer_seat":7,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"PPC","players":[{"id":1,"seat":1,"name":"Agapito","starting_stack":9.7},{"id":3,"seat":3,"name":"anb9bJHyuj","starting_stack":4.45},{"id":4,"seat":4,"name":"DubNation","starting_stack":20.01},{"id":5,"seat":5,"name":"CFFl2rCOze","starting_stack":10.37},{"id":6,"seat":6,"name":"-c6EEVvXCE","starting_stack":3.47},{"id":7,"seat":7,"name":"E9V-2MDLwt","starting_stack":9.1},{"id":8,"seat":8,"name":"JzhSREGpIj","starting_stack":26.55},{"id":9,"seat":9,"name":"Aheadboat","starting_stack":6.3}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":5,"action":"Dealt Cards","cards":["2h","5s"]},{"action_number":1,"player_id":8,"action":"Dealt Cards","cards":["Ah","Ks"]},{"action_number":2,"player_id":8,"action":"Post SB","amount":0.05},{"action_number":3,"player_id":9,"action":"Post BB","amount":0.1},{"action_number":4,"player_id":1,"action":"Post Dead","amount":0.05},{"action_number":5,"player_id":1,"action":"Post Dead","amount":0.1},{"action_number":6,"player_id":1,"action":"Check"},{"action_number":7,"player_id":3,"action":"Fold"},{"action_number":8,"player_id":4,"action":"Fold"},{"action_number":9,"player_id":5,"action":"Fold"},{"action_number":10,"player_id":6,"action":"Fold"},{"action_number":11,"player_id":7,"action":"Fold"},{"action_number":12,"player_id":8,"action":"Raise","amount":0.25},{"action_number":13,"player_id":9,"action":"Fold"},{"action_number":14,"player_id":1,"action":"Call","amount":0.25}]},{"id":1,"cards":["5d","6s","6d"],"street":"Flop","actions":[{"action_number":0,"player_id":8,"action":"Check"},{"action_number":1,"player_id":1,"action":"Raise","amount":0.37},{"action_number":2,"player_id":8,"action":"Call","amount":0.37}]},{"id":2,"cards":["9d"],"street":"Turn","actions":[{"action_number":0,"player_id":8,"action":"Check"},{"action_number":1,"player_id":1,"action":"Raise","amount":9.03,"is_allin":true},{"action_number":2,"player_id":8,"action":"Fold"},{"action_number":3,"player_id":8,"action":"Shows Cards","cards":["Ah","Ks"]},{"action_number":4,"player_id":5,"action":"Shows Cards","cards":["2h","5s"]}]}],"pots":[{"number":0,"amount":1.49,"rake":0,"player_wins":[{"player_id":1,"win_amount":1.49}]}]}}"#;
+        let report = convert_ohh_file(amit).unwrap();
+        assert!(report.output.contains("Agapito: calls 0.20\n*** FLOP ***"));
+        assert!(!report.output.contains("returned to Agapito\n*** FLOP"));
+        assert!(report.output.contains("Total pot 1.49 | Rake 0.00"));
+        assert!(
+            !report.diagnostics.iter().any(|d| d.to_string().contains("pots total")),
+            "{:?}",
+            report.diagnostics
+        );
+    }
+
+    #[test]
+    fn test_omaha_pot_limit_header_and_cards() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"plo1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"TestTable","table_size":6,"dealer_seat":2,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","game_type":"Omaha","bet_limit":{"bet_type":"PL"},"hero_player_id":1,"players":[{"id":1,"seat":1,"name":"Hero","starting_stack":10},{"id":2,"seat":2,"name":"Villain","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Dealt Cards","cards":["Ah","Kh","Qc","2s"]},{"action_number":1,"player_id":2,"action":"Post SB","amount":0.05},{"action_number":2,"player_id":1,"action":"Post BB","amount":0.1},{"action_number":3,"player_id":2,"action":"Call","amount":0.05},{"action_number":4,"player_id":1,"action":"Check"}]},{"id":1,"street":"Flop","cards":["3h","7h","9h"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"}]},{"id":2,"street":"Turn","cards":["Jd"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"}]},{"id":3,"street":"River","cards":["4c"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"},{"action_number":2,"player_id":1,"action":"Shows Cards","cards":["Ah","Kh","Qc","2s"]}]}],"pots":[{"number":0,"amount":0.2,"rake":0,"player_wins":[{"player_id":1,"win_amount":0.2}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== PLO OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.starts_with("PokerStars Hand #plo1: Omaha Pot Limit ($0.05/$0.10 USD)"));
+        assert!(output.contains("Dealt to Hero [Ah Kh Qc 2s]"));
+        assert!(output.contains("Hero: shows [Ah Kh Qc 2s] (a flush, Ace high)"));
+    }
+
+    #[test]
+    fn test_tournament_hand() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"t100","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"987654 3","table_size":9,"dealer_seat":1,"small_blind_amount":50,"big_blind_amount":100,"currency":"USD","tournament":true,"tournament_info":{"tournament_number":987654,"buyin_amount":10,"fee_amount":1,"bounty_fee_amount":5,"currency":"USD","level":4},"players":[{"id":1,"seat":1,"name":"Player1","starting_stack":1500,"player_bounty":5},{"id":2,"seat":2,"name":"Player2","starting_stack":3250,"player_bounty":7.5}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":50},{"action_number":1,"player_id":2,"action":"Post BB","amount":100},{"action_number":2,"player_id":1,"action":"Raise","amount":250},{"action_number":3,"player_id":2,"action":"Fold"}]}],"pots":[{"number":0,"amount":200,"rake":0,"player_wins":[{"player_id":1,"win_amount":200}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        println!("\n=== TOURNAMENT OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(output.starts_with(
+            "PokerStars Hand #t100: Tournament #987654, $10.00+$5.00+$1.00 USD Hold'em No Limit - Level IV (50/100) - 2023-12-05 02:50:49 UTC"
+        ));
+        assert!(output.contains("Seat 1: Player1 (1500 in chips, $5.00 bounty)"));
+        assert!(output.contains("Player1: raises 200 to 300"));
+        assert!(output.contains("Uncalled bet (200) returned to Player1"));
+        assert!(output.contains("Total pot 200 | Rake 0"));
+    }
+
+    #[test]
+    fn test_antes_posted_before_blinds() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"ante1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"TestTable","table_size":6,"dealer_seat":3,"small_blind_amount":0.05,"big_blind_amount":0.1,"ante_amount":0.02,"currency":"USD","players":[{"id":1,"seat":1,"name":"Player1","starting_stack":10},{"id":2,"seat":2,"name":"Player2","starting_stack":10},{"id":3,"seat":3,"name":"Player3","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Post Ante","amount":0.02},{"action_number":3,"player_id":2,"action":"Post Ante","amount":0.02},{"action_number":4,"player_id":3,"action":"Post Ante","amount":0.02},{"action_number":5,"player_id":3,"action":"Raise","amount":0.3},{"action_number":6,"player_id":1,"action":"Fold"},{"action_number":7,"player_id":2,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.31,"rake":0,"player_wins":[{"player_id":3,"win_amount":0.31}]}]}}"#;
+
+        let report = convert_ohh_file(input).unwrap();
+        let output = report.output;
+        println!("\n=== ANTE OUTPUT ===\n{}\n=== END ===\n", output);
+
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+
+        assert!(output.contains("Hold'em No Limit ($0.05/$0.10 - Ante $0.02 USD)"));
+        assert!(output.contains(
+            "Player1: posts the ante $0.02\n\
+             Player2: posts the ante $0.02\n\
+             Player3: posts the ante $0.02\n\
+             Player1: posts small blind $0.05\n\
+             Player2: posts big blind $0.10\n*** HOLE CARDS ***"
+        ));
+        assert!(output.contains("Player3: raises $0.20 to $0.30"));
+        assert!(output.contains("Uncalled bet ($0.20) returned to Player3"));
+        assert!(output.contains("\nTotal pot $0.31 | Rake $0.00\n"));
+
+        // antes only recorded on the hand are posted for every seat
+        let implied = input.replace(
+            r#"{"action_number":2,"player_id":1,"action":"Post Ante","amount":0.02},{"action_number":3,"player_id":2,"action":"Post Ante","amount":0.02},{"action_number":4,"player_id":3,"action":"Post Ante","amount":0.02},"#,
+            "",
+        );
+        let report = convert_ohh_file(&implied).unwrap();
+        assert!(report.diagnostics.is_empty(), "{:?}", report.diagnostics);
+        let output = report.output;
+        assert_eq!(output.matches("posts the ante $0.02").count(), 3);
+    }
+
+    #[test]
+    fn test_report_lists_hands_and_warnings() {
+        let good = r#"{"ohh":{"game_number":"ok1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"A","starting_stack":10},{"id":2,"seat":2,"name":"B","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.1,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.1}]}]}}"#;
+        let odd = good
+            .replace("ok1", "odd1")
+            .replace(r#""player_id":1,"action":"Fold""#, r#""player_id":9,"action":"Sit Out""#)
+            .replace(r#""amount":0.1,"rake""#, r#""amount":0.2,"rake""#)
+            .replace(r#""player_id":2,"win_amount""#, r#""player_id":3,"win_amount""#);
+        let input = format!("{}\n{{\"ohh\": nope}}\n{}\n", good, odd);
+
+        let report = convert_ohh_file(&input).unwrap();
+        assert_eq!(report.hands, vec!["ok1", "odd1"]);
+        assert_eq!(report.skipped(), 1);
+
+        let kinds: Vec<&Diagnostic> = report.diagnostics.iter().collect();
+        assert!(matches!(kinds[0], Diagnostic::ParseError { index: 1, line: 2, .. }));
+        assert!(kinds.contains(&&Diagnostic::UnknownAction {
+            game_number: "odd1".to_string(),
+            action_number: 2,
+            action: "Sit Out".to_string(),
+        }));
+        assert!(kinds.contains(&&Diagnostic::InconsistentPot {
+            game_number: "odd1".to_string(),
+            expected: Money::from(0.1),
+            actual: Money::from(0.2),
+        }));
+        assert!(kinds.contains(&&Diagnostic::MissingPlayerId {
+            game_number: "odd1".to_string(),
+            action_number: None,
+            player_id: Some("3".to_string()),
+        }));
+        assert!(report.diagnostics.iter().all(|d| d.game_number() != Some("ok1")));
+        assert_eq!(report.warnings.len(), report.diagnostics.len());
+
+        match convert_ohh_file("{\"ohh\": nope}") {
+            Err(ConvertError::NoHands(diagnostics)) => assert_eq!(diagnostics.len(), 1),
+            other => panic!("expected no hands, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_strict_mode_refuses_invalid_hands() {
+        let good = r#"{"ohh":{"game_number":"ok1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"A","starting_stack":10},{"id":2,"seat":2,"name":"B","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.1,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.1}]}]}}"#;
+        let over_stack = good
+            .replace("ok1", "bad1")
+            .replace(r#""player_id":2,"action":"Post BB","amount":0.1"#, r#""player_id":2,"action":"Post BB","amount":12"#);
+        let input = format!("{}\n{}\n", good, over_stack);
+
+        let lenient = convert_ohh_file(&input).unwrap();
+        assert_eq!(lenient.hands, vec!["ok1", "bad1"]);
+        assert!(lenient
+            .warnings
+            .contains(&"hand bad1: player 2 puts in 12 with a starting stack of 10".to_string()));
+
+        let strict = ConvertOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let report = convert_ohh_file_with(&input, &strict).unwrap();
+        assert_eq!(report.hands, vec!["ok1"]);
+        assert!(!report.output.contains("bad1"));
+        assert!(matches!(
+            &report.diagnostics[0],
+            Diagnostic::RejectedHand { game_number, .. } if game_number == "bad1"
+        ));
+
+        match convert_ohh_file_with(&over_stack, &strict) {
+            Err(ConvertError::Rejected(diagnostics)) => assert_eq!(diagnostics.len(), 1),
+            other => panic!("expected the hand to be refused, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_overflowing_amounts_are_reported() {
+        let good = r#"{"ohh":{"game_number":"ok1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"A","starting_stack":10},{"id":2,"seat":2,"name":"B","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.1,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.1}]}]}}"#;
+        let huge = good.replace("ok1", "huge1").replace(
+            r#""pots":[{"number":0,"amount":0.1,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.1}]}]"#,
+            r#""pots":[{"number":0,"amount":900000000000000,"rake":0,"player_wins":[]},{"number":1,"amount":900000000000000,"rake":0,"player_wins":[]}]"#,
+        );
+        let input = format!("{}\n{}\n", good, huge);
+
+        let report = convert_ohh_file(&input).unwrap();
+        assert_eq!(report.hands, vec!["ok1"]);
+        assert!(!report.output.contains("huge1"));
+        assert!(report.diagnostics.contains(&Diagnostic::AmountOverflow {
+            game_number: "huge1".to_string()
+        }));
+
+        // the writer alone saturates instead of panicking
+        let (hands, _) = parse_ohh_chunks(&huge).unwrap();
+        assert!(total_pot_line(&hands[0]).starts_with("Total pot $922,337,203,685,477.58"));
+    }
+
+    #[test]
+    fn test_hero_option() {
+        let input = r#"{"ohh":{"game_number":"h1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Ann","display":"annie","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Dealt Cards","cards":["Ah","Kh"]},{"action_number":3,"player_id":2,"action":"Dealt Cards","cards":["7c","2d"]},{"action_number":4,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.15}]}]}}"#;
+
+        let all = convert_ohh_file(input).unwrap().output;
+        assert!(!all.contains("Dealt to"));
+
+        for hero in ["annie", "Ann", "1"] {
+            let options = ConvertOptions {
+                hero: Some(hero.to_string()),
+                ..Default::default()
+            };
+            let output = convert_ohh_file_with(input, &options).unwrap().output;
+            assert!(output.contains("Dealt to Ann [Ah Kh]\n"));
+            assert!(!output.contains("7c 2d"));
+        }
+
+        let options = ConvertOptions {
+            hero: Some("Carl".to_string()),
+            ..Default::default()
+        };
+        let report = convert_ohh_file_with(input, &options).unwrap();
+        assert!(!report.output.contains("Dealt to"));
+        assert!(report.warnings.iter().any(|w| w.contains("h1: Carl is not seated")));
+    }
+
+    #[test]
+    fn test_anonymize_option() {
+        let input = r#"{"ohh":{"game_number":"a1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"Zurich","table_handle":"zrh-1","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Ann","display":"annie","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Dealt Cards","cards":["Ah","Kh"]},{"action_number":3,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.15}]}]}}"#;
+        let options = ConvertOptions {
+            hero: Some("annie".to_string()),
+            anonymize: Some(AnonymizeOptions {
+                keep_hero: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let output = convert_ohh_file_with(input, &options).unwrap().output;
+        assert!(output.contains("Table 'Table1' 6-max"));
+        assert!(output.contains("Dealt to Ann [Ah Kh]"));
+        assert!(output.contains("Player1: posts big blind $0.10"));
+        assert!(!output.contains("Bob") && !output.contains("Zurich"));
+
+        let ohh = normalize_ohh_file_with(input, &options).unwrap().output;
+        assert!(ohh.contains(r#""name":"Player1""#));
+        assert!(!ohh.contains("Bob") && !ohh.contains("zrh-1"));
+    }
+
+    #[test]
+    fn test_display_names_option() {
+        let input = r#"{"ohh":{"game_number":"d1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","hero_player_id":1,"players":[{"id":1,"seat":1,"name":"CFFl2rCOze","display":"bella","starting_stack":10},{"id":2,"seat":2,"name":"-c6EEVvXCE","display":"bella","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Dealt Cards","cards":["Ah","Kh"]},{"action_number":3,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.15}]}]}}"#;
+        let options = ConvertOptions {
+            names: NamePolicy::Display,
+            aliases: [("-c6EEVvXCE".to_string(), "bdawg (reg)".to_string())].into(),
+            ..Default::default()
+        };
+
+        let output = convert_ohh_file_with(input, &options).unwrap().output;
+        assert!(output.contains("Dealt to bella [Ah Kh]"));
+        assert!(output.contains("bdawg {reg}: posts big blind $0.10"));
+        assert!(output.contains("Seat 2: bdawg {reg} (big blind) collected ($0.15)"));
+        assert!(!output.contains("CFFl2rCOze"));
+    }
+
+    #[test]
+    fn test_filter_option() {
+        let hand = |game: &str, villain: &str| {
+            format!(
+                r#"{{"ohh":{{"game_number":"{}","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"players":[{{"id":1,"seat":1,"name":"Ann","starting_stack":10}},{{"id":2,"seat":2,"name":"{}","starting_stack":10}}],"rounds":[{{"id":0,"street":"Preflop","actions":[{{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05}},{{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1}},{{"action_number":2,"player_id":1,"action":"Fold"}}]}}],"pots":[{{"number":0,"amount":0.15,"rake":0,"player_wins":[{{"player_id":2,"win_amount":0.15}}]}}]}}}}"#,
+                game, villain
+            )
+        };
+        let input = [hand("f1", "Bob"), hand("f2", "Cy"), hand("f3", "Bob")].join("\n");
+        let options = |filter: &str| ConvertOptions {
+            hero: Some("Ann".to_string()),
+            filter: Some(filter.to_string()),
+            ..Default::default()
+        };
+
+        let report = convert_ohh_file_with(&input, &options("player bob")).unwrap();
+        assert_eq!(report.hands, ["f1", "f3"]);
+        let report = normalize_ohh_file_with(&input, &options("not player bob")).unwrap();
+        assert_eq!(report.hands, ["f2"]);
+
+        let (hands, _) = parse_ohh_chunks(&input).unwrap();
+        let count = count_matches(hands, &options("player Cy or player Dee")).unwrap();
+        assert_eq!((count.matched, count.total), (1, 3));
+
+        assert_eq!(
+            convert_ohh_file_with(&input, &options("saw flop")).unwrap_err(),
+            ConvertError::NothingMatched
+        );
+        let err = convert_ohh_file_with(&input, &options("pot >")).unwrap_err();
+        assert!(err.to_string().starts_with("invalid filter: "));
+    }
+
+    #[test]
+    fn test_detect_hero() {
+        let hand = |game: &str, names: &[&str]| {
+            let players: Vec<String> = names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| format!(r#"{{"id":{},"seat":{},"name":"{}","starting_stack":10}}"#, i + 1, i + 1, n))
+                .collect();
+            let json = format!(
+                r#"{{"game_number":"{}","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"players":[{}],"rounds":[],"pots":[]}}"#,
+                game,
+                players.join(",")
+            );
+            serde_json::from_str::<OhhHand>(&json).unwrap()
+        };
+
+        let hands = vec![
+            hand("1", &["Ann", "Bob"]),
+            hand("2", &["Bob", "Carl"]),
+            hand("3", &["Dee", "Bob"]),
+        ];
+        assert_eq!(detect_hero(&hands).as_deref(), Some("Bob"));
+
+        let tied = vec![hand("1", &["Ann", "Bob"]), hand("2", &["Ann", "Bob"])];
+        assert_eq!(detect_hero(&tied), None);
+    }
+
+    #[test]
+    fn test_other_hole_cards_only_at_showdown() {
+        let input = r#"{"ohh":{"game_number":"sd1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","hero_player_id":1,"players":[{"id":1,"seat":1,"name":"Ann","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10},{"id":3,"seat":3,"name":"Cy","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":1,"action":"Dealt Cards","cards":["Ah","Kh"]},{"action_number":1,"player_id":2,"action":"Dealt Cards","cards":["7c","7d"]},{"action_number":2,"player_id":3,"action":"Dealt Cards","cards":["9s","2d"]},{"action_number":3,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":4,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":5,"player_id":3,"action":"Fold"},{"action_number":6,"player_id":1,"action":"Call","amount":0.05},{"action_number":7,"player_id":2,"action":"Check"}]},{"id":1,"street":"Flop","cards":["2c","5h","Jd"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"}]},{"id":2,"street":"Turn","cards":["Qs"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"}]},{"id":3,"street":"River","cards":["3d"],"actions":[{"action_number":0,"player_id":1,"action":"Check"},{"action_number":1,"player_id":2,"action":"Check"},{"action_number":2,"player_id":1,"action":"Shows Cards","cards":["Ah","Kh"]}]}],"pots":[{"number":0,"amount":0.2,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.2}]}]}}"#;
+
+        let output = convert_ohh_file(input).unwrap().output;
+        assert!(output.contains("*** HOLE CARDS ***\nDealt to Ann [Ah Kh]\nCy: folds"));
+        assert!(output.contains(
+            "*** SHOW DOWN ***\nAnn: shows [Ah Kh] (high card Ace)\nBob: shows [7c 7d] (a pair of Sevens)\n"
+        ));
+        assert!(output.contains("Seat 2: Bob (big blind) showed [7c 7d] and won ($0.20) with a pair of Sevens"));
+        assert!(!output.contains("9s"));
+    }
+
+    #[test]
+    fn test_pot_labels() {
+        let input = r#"{"ohh":{"game_number":"labels","start_date_utc":"2023-12-05T06:23:28.158Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"players":[],"rounds":[],"pots":[{"number":0,"amount":1,"rake":0,"player_wins":[]},{"number":1,"amount":1,"rake":0,"player_wins":[]},{"number":2,"amount":1,"rake":0,"player_wins":[]}]}}"#;
+        let hand = serde_json::from_str::<OhhFile>(input).unwrap().ohh;
+
+        assert_eq!(pot_label(&hand, 0), "main pot");
+        assert_eq!(pot_label(&hand, 1), "side pot-1");
+        assert_eq!(pot_label(&hand, 2), "side pot-2");
+        assert_eq!(
+            total_pot_line(&hand),
+            "Total pot $3.00 Main pot $1.00. Side pot-1 $1.00. Side pot-2 $1.00. | Rake $0.00"
+        );
+    }
+
+    #[test]
+    fn test_normalize_keeps_unknown_fields() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","internal_version":"1.0.0","site_name":"iPoker","game_number":"n1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_skin":"","table_size":6,"dealer_seat":2,"small_blind_amount":0.05,"big_blind_amount":0.1,"ante_amount":0,"flags":["Observed"],"seat_map":"x","bet_limit":{"bet_type":"NL","bet_cap":0},"hero_player_id":"bob","players":[{"id":"bob","seat":2,"name":"Bob","starting_stack":10,"country":"NZ"},{"id":"ann","seat":1,"name":"Ann","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":1,"player_id":"bob","action":"Post BB","amount":0.1,"timestamp":12},{"action_number":0,"player_id":"ann","action":"Post SB","amount":0.05},{"action_number":2,"player_id":"ann","action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":"bob","win_amount":0.15,"cashout_fee":0}]}]}}"#;
+
+        let report = normalize_ohh_file(input).unwrap();
+        assert_eq!(report.hands, vec!["n1"]);
+
+        let json: Value = serde_json::from_str(report.output.trim()).unwrap();
+        let ohh = &json["ohh"];
+        assert_eq!(ohh["spec_version"], "1.4.3");
+        assert_eq!(ohh["internal_version"], "1.0.0");
+        assert_eq!(ohh["table_skin"], "");
+        assert_eq!(ohh["flags"][0], "Observed");
+        assert_eq!(ohh["seat_map"], "x");
+        assert_eq!(ohh["bet_limit"]["bet_cap"], 0);
+        assert_eq!(ohh["ante_amount"], 0);
+        assert_eq!(ohh["tournament"], false);
+
+        // ids become numbers in seat order and every reference follows
+        assert_eq!(ohh["hero_player_id"], 2);
+        assert_eq!(ohh["players"][0]["id"], 1);
+        assert_eq!(ohh["players"][1]["country"], "NZ");
+        let actions = &ohh["rounds"][0]["actions"];
+        assert_eq!(actions[0]["action"], "Post SB");
+        assert_eq!(actions[0]["player_id"], 1);
+        assert_eq!(actions[1]["timestamp"], 12);
+        assert_eq!(ohh["pots"][0]["player_wins"][0]["player_id"], 2);
+        assert_eq!(ohh["pots"][0]["player_wins"][0]["cashout_fee"], 0);
+
+        // and it still converts, now in action order
+        assert!(convert_ohh_file(&report.output)
+            .unwrap()
+            .output
+            .contains("Ann: posts small blind $0.05\nBob: posts big blind $0.10\n"));
+    }
+
+    #[test]
+    fn test_normalize_keeps_padded_ids_apart() {
+        let input = r#"{"ohh":{"spec_version":"1.4.3","site_name":"iPoker","game_number":"n2","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":2,"small_blind_amount":0.05,"big_blind_amount":0.1,"hero_player_id":"07","players":[{"id":"7","seat":1,"name":"Ann","starting_stack":10},{"id":"07","seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","actions":[{"action_number":0,"player_id":"7","action":"Post SB","amount":0.05},{"action_number":1,"player_id":"07","action":"Post BB","amount":0.1},{"action_number":2,"player_id":"7","action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":"07","win_amount":0.15}]}]}}"#;
+
+        // a padded id is not written as a number it would collide with
+        let hand = serde_json::from_str::<OhhFile>(input).unwrap().ohh;
+        let json: Value = serde_json::from_str(&ohh_json(hand).unwrap()).unwrap();
+        assert_eq!(json["ohh"]["players"][0]["id"], 7);
+        assert_eq!(json["ohh"]["players"][1]["id"], "07");
+
+        // normalizing renumbers both players instead
+        let report = normalize_ohh_file(input).unwrap();
+        let json: Value = serde_json::from_str(report.output.trim()).unwrap();
+        let ohh = &json["ohh"];
+        assert_eq!(ohh["players"][0]["id"], 1);
+        assert_eq!(ohh["players"][1]["id"], 2);
+        assert_eq!(ohh["hero_player_id"], 2);
+        assert_eq!(ohh["rounds"][0]["actions"][2]["player_id"], 1);
+        assert_eq!(ohh["pots"][0]["player_wins"][0]["player_id"], 2);
     }
 
     #[test]
@@ -725,10 +2292,15 @@ mod tests {
 
         assert!(result.is_ok(), "Conversion failed: {:?}", result.err());
 
-        let output = result.unwrap();
+        let report = result.unwrap();
+        let output = report.output;
         let hand_count = output.matches("PokerStars Hand #").count();
 
         println!("Successfully converted {} hands", hand_count);
+        println!("{} warnings", report.warnings.len());
+        for warning in report.warnings.iter().take(10) {
+            println!("  {}", warning);
+        }
         println!(
             "Output size: {} bytes ({:.1} KB)",
             output.len(),
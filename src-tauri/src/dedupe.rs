@@ -0,0 +1,319 @@
+use log::info;
+use std::collections::HashMap;
+
+use crate::converter::{is_post, seat_by_id, Action, Extra, OhhHand};
+use crate::diagnostics::Diagnostic;
+
+// hands without a number of their own cannot be told apart
+const UNNUMBERED: &str = "unknown";
+
+fn key(h: &OhhHand) -> Option<(String, String)> {
+    (h.game_number != UNNUMBERED).then(|| {
+        (
+            h.site_name.clone().unwrap_or_default(),
+            h.game_number.clone(),
+        )
+    })
+}
+
+fn has_cards(a: &Action) -> bool {
+    a.cards.as_ref().is_some_and(|c| !c.is_empty())
+}
+
+// known cards count most, then how much of the action was recorded
+fn completeness(h: &OhhHand) -> (usize, usize, usize, bool) {
+    let actions = h.rounds.iter().flat_map(|r| &r.actions);
+    let known = actions.clone().filter(|a| has_cards(a)).count();
+    let board = h.rounds.iter().map(|r| r.cards.len()).sum();
+    (known, actions.count(), board, h.hero_player_id.is_some())
+}
+
+// copies what `other` knows and `base` does not into `base`. players are
+// matched by seat, since ids and names can differ between exports
+fn fill_from(base: &mut OhhHand, other: &OhhHand, merged: &mut Vec<String>) {
+    let mut note = |what: String| {
+        if !merged.contains(&what) {
+            merged.push(what);
+        }
+    };
+    let id_at = |h: &OhhHand, seat: u8| {
+        h.players
+            .iter()
+            .find(|p| p.seat == seat)
+            .map(|p| p.id.clone())
+    };
+    let name_at = |h: &OhhHand, seat: u8| {
+        h.players
+            .iter()
+            .find(|p| p.seat == seat)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| format!("seat {}", seat))
+    };
+
+    if base.hero_player_id.is_none() {
+        let seat = other
+            .hero_player_id
+            .as_deref()
+            .map(|id| seat_by_id(other, id));
+        if let Some(id) = seat.and_then(|seat| id_at(base, seat)) {
+            base.hero_player_id = Some(id);
+            note("the hero".to_string());
+        }
+    }
+
+    for p in &mut base.players {
+        let display = other
+            .players
+            .iter()
+            .find(|o| o.seat == p.seat)
+            .and_then(|o| o.display.clone());
+        if p.display.is_none() && display.is_some() {
+            p.display = display;
+            note(format!("the display name of {}", p.name));
+        }
+    }
+
+    for round in &other.rounds {
+        let Some(index) = base.rounds.iter().position(|r| r.street == round.street) else {
+            continue;
+        };
+        if base.rounds[index].cards.is_empty() && !round.cards.is_empty() {
+            base.rounds[index].cards = round.cards.clone();
+            note(format!("the {} cards", round.street.to_lowercase()));
+        }
+
+        for a in round.actions.iter().filter(|a| has_cards(a)) {
+            let Some(seat) = a.player_id.as_deref().map(|id| seat_by_id(other, id)) else {
+                continue;
+            };
+            let Some(pid) = id_at(base, seat) else {
+                continue;
+            };
+            let actions = &mut base.rounds[index].actions;
+            let same = actions
+                .iter_mut()
+                .find(|b| b.action == a.action && b.player_id.as_deref() == Some(pid.as_str()));
+            match same {
+                Some(b) if !has_cards(b) => b.cards = a.cards.clone(),
+                Some(_) => continue,
+                // hole cards go after the posts and the other deals, shown
+                // and mucked cards only where the copy has the action
+                None if a.action == "Dealt Cards" => {
+                    let at = actions
+                        .iter()
+                        .position(|b| !is_post(&b.action) && b.action != "Dealt Cards")
+                        .unwrap_or(actions.len());
+                    let number = match actions.get(at) {
+                        Some(b) => b.action_number,
+                        None => actions.iter().map(|b| b.action_number + 1).max().unwrap_or(0),
+                    };
+                    for b in &mut actions[at..] {
+                        b.action_number += 1;
+                    }
+                    actions.insert(at, Action {
+                        action_number: number,
+                        player_id: Some(pid),
+                        action: a.action.clone(),
+                        amount: None,
+                        is_allin: None,
+                        cards: a.cards.clone(),
+                        extra: Extra::new(),
+                    });
+                }
+                None => continue,
+            }
+            note(format!("the cards of {}", name_at(base, seat)));
+        }
+    }
+}
+
+// keeps one copy of every hand that appears more than once across `groups`,
+// where the first copy was, and merges into it whatever the others add.
+// each diagnostic comes with the index of the group holding the kept copy
+pub fn dedupe_groups(groups: &mut [&mut Vec<OhhHand>]) -> Vec<(usize, Diagnostic)> {
+    let mut first: HashMap<(String, String), (usize, usize)> = HashMap::new();
+    let mut copies: HashMap<(String, String), Vec<OhhHand>> = HashMap::new();
+    for (g, hands) in groups.iter_mut().enumerate() {
+        let mut kept = Vec::with_capacity(hands.len());
+        for hand in hands.drain(..) {
+            match key(&hand) {
+                Some(k) if first.contains_key(&k) => copies.entry(k).or_default().push(hand),
+                Some(k) => {
+                    first.insert(k, (g, kept.len()));
+                    kept.push(hand);
+                }
+                None => kept.push(hand),
+            }
+        }
+        **hands = kept;
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut keys: Vec<_> = copies.into_iter().collect();
+    keys.sort_by_key(|(k, _)| first[k]);
+    for (k, others) in keys {
+        let (g, i) = first[&k];
+        let kept = &mut groups[g][i];
+
+        let mut all: Vec<OhhHand> = others;
+        all.push(kept.clone());
+        let best = (0..all.len())
+            .max_by_key(|&j| completeness(&all[j]))
+            .unwrap_or(0);
+        let mut merged_hand = all.swap_remove(best);
+        let mut merged = Vec::new();
+        for other in &all {
+            fill_from(&mut merged_hand, other, &mut merged);
+        }
+        *kept = merged_hand;
+
+        info!("hand {}: {} duplicate copies", k.1, all.len());
+        diagnostics.push((
+            g,
+            Diagnostic::DuplicateHand {
+                game_number: k.1,
+                dropped: all.len(),
+                merged,
+            },
+        ));
+    }
+    diagnostics
+}
+
+pub fn dedupe_hands(hands: &mut Vec<OhhHand>) -> Vec<Diagnostic> {
+    dedupe_groups(&mut [hands])
+        .into_iter()
+        .map(|(_, d)| d)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::FOLDED;
+
+    fn hand(site: &str, game: &str, hero: bool, dealt: &[(u32, &str)]) -> OhhHand {
+        let mut h = crate::fixtures::hand(FOLDED, game);
+        h.site_name = Some(site.to_string());
+        if hero {
+            h.hero_player_id = Some("1".to_string());
+        }
+        let actions = &mut h.rounds[0].actions;
+        let mut fold = actions.pop().unwrap();
+        for (pid, cards) in dealt {
+            let deal = format!(
+                r#"{{"action_number":{},"player_id":{},"action":"Dealt Cards","cards":{}}}"#,
+                pid + 1,
+                pid,
+                cards
+            );
+            actions.push(serde_json::from_str(&deal).unwrap());
+        }
+        fold.action_number = 5;
+        actions.push(fold);
+        h
+    }
+
+    fn dealt(h: &OhhHand) -> Vec<(String, String)> {
+        h.rounds[0]
+            .actions
+            .iter()
+            .filter(|a| a.action == "Dealt Cards")
+            .map(|a| {
+                let cards = a.cards.clone().unwrap_or_default().join(" ");
+                (a.player_id.clone().unwrap_or_default(), cards)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merges_copies_across_groups() {
+        let mut monday = vec![
+            hand("iPoker", "g1", false, &[(1, "[]")]),
+            hand("iPoker", "g2", false, &[]),
+        ];
+        let mut tuesday = vec![
+            hand(
+                "iPoker",
+                "g1",
+                true,
+                &[(1, r#"["Ah","Kh"]"#), (2, r#"["7c","2d"]"#)],
+            ),
+            hand("iPoker", "g2", false, &[]),
+            hand("other", "g2", false, &[]),
+            hand("iPoker", "g3", false, &[]),
+        ];
+
+        let found = dedupe_groups(&mut [&mut monday, &mut tuesday]);
+        let games = |hands: &[OhhHand]| {
+            hands
+                .iter()
+                .map(|h| h.game_number.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(games(&monday), ["g1", "g2"]);
+        assert_eq!(games(&tuesday), ["g2", "g3"]);
+        assert_eq!(tuesday[0].site_name.as_deref(), Some("other"));
+
+        // the most complete copy is kept, in the place of the first one
+        assert_eq!(monday[0].hero_player_id.as_deref(), Some("1"));
+        assert_eq!(dealt(&monday[0]).len(), 2);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, 0);
+        assert_eq!(found[0].1.to_string(), "hand g1: dropped 1 duplicate copy");
+        assert_eq!(found[1].1.to_string(), "hand g2: dropped 1 duplicate copy");
+    }
+
+    #[test]
+    fn test_fills_gaps_from_other_copies() {
+        let mut hands = vec![
+            hand("iPoker", "g1", true, &[(1, r#"["Ah","Kh"]"#)]),
+            hand("iPoker", "g1", false, &[(1, "[]"), (2, r#"["7c","2d"]"#)]),
+            hand("iPoker", "g1", false, &[]),
+        ];
+        let found = dedupe_hands(&mut hands);
+        assert_eq!(hands.len(), 1);
+        assert_eq!(
+            dealt(&hands[0]),
+            [
+                ("1".to_string(), "Ah Kh".to_string()),
+                ("2".to_string(), "7c 2d".to_string())
+            ]
+        );
+        assert_eq!(
+            found[0].to_string(),
+            "hand g1: dropped 2 duplicate copies, merging in the hero and the cards of Ann"
+        );
+
+        // missing hole cards go after the posts and the deals already there
+        let mut hands = vec![
+            hand("iPoker", "g1", true, &[(1, r#"["Ah","Kh"]"#)]),
+            hand("iPoker", "g1", false, &[(2, r#"["7c","2d"]"#)]),
+        ];
+        dedupe_hands(&mut hands);
+        let order: Vec<(u32, &str)> = hands[0].rounds[0]
+            .actions
+            .iter()
+            .map(|a| (a.action_number, a.action.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (0, "Post SB"),
+                (1, "Post BB"),
+                (2, "Dealt Cards"),
+                (5, "Dealt Cards"),
+                (6, "Fold")
+            ]
+        );
+
+        // numberless hands are never merged
+        let mut hands = vec![
+            hand("iPoker", "unknown", false, &[]),
+            hand("iPoker", "unknown", false, &[]),
+        ];
+        assert!(dedupe_hands(&mut hands).is_empty());
+        assert_eq!(hands.len(), 2);
+    }
+}
@@ -0,0 +1,384 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+use crate::framing::FrameError;
+use crate::money::Money;
+use crate::split::{split_output, HandSpan, OutputPart, SplitOptions};
+use crate::validate::Issue;
+
+// something the user should know about, attached to the hand it came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Diagnostic {
+    ParseError {
+        index: usize,
+        offset: usize,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnknownAction {
+        game_number: String,
+        action_number: u32,
+        action: String,
+    },
+    InconsistentPot {
+        game_number: String,
+        expected: Money,
+        actual: Money,
+    },
+    AmountOverflow {
+        game_number: String,
+    },
+    MissingPlayerId {
+        game_number: String,
+        action_number: Option<u32>,
+        player_id: Option<String>,
+    },
+    InvalidHand {
+        game_number: String,
+        issue: Issue,
+    },
+    RejectedHand {
+        game_number: String,
+        issues: Vec<Issue>,
+    },
+    HeroNotSeated {
+        game_number: String,
+        hero: String,
+    },
+    DuplicateHand {
+        game_number: String,
+        dropped: usize,
+        merged: Vec<String>,
+    },
+    // the start time is kept as printed
+    UnknownTimeZone {
+        game_number: String,
+        zone: String,
+    },
+}
+
+impl Diagnostic {
+    pub fn game_number(&self) -> Option<&str> {
+        match self {
+            Diagnostic::ParseError { .. } => None,
+            Diagnostic::UnknownAction { game_number, .. }
+            | Diagnostic::InconsistentPot { game_number, .. }
+            | Diagnostic::AmountOverflow { game_number }
+            | Diagnostic::MissingPlayerId { game_number, .. }
+            | Diagnostic::InvalidHand { game_number, .. }
+            | Diagnostic::RejectedHand { game_number, .. }
+            | Diagnostic::HeroNotSeated { game_number, .. }
+            | Diagnostic::DuplicateHand { game_number, .. }
+            | Diagnostic::UnknownTimeZone { game_number, .. } => Some(game_number),
+        }
+    }
+
+    pub fn from_issue(game_number: &str, issue: Issue) -> Self {
+        let game_number = game_number.to_string();
+        match issue {
+            Issue::MissingPlayerId { action_number, .. } => Diagnostic::MissingPlayerId {
+                game_number,
+                action_number: Some(action_number),
+                player_id: None,
+            },
+            Issue::UnknownPlayer {
+                action_number,
+                player_id,
+                ..
+            } => Diagnostic::MissingPlayerId {
+                game_number,
+                action_number: Some(action_number),
+                player_id: Some(player_id),
+            },
+            Issue::UnknownWinner { player_id, .. } => Diagnostic::MissingPlayerId {
+                game_number,
+                action_number: None,
+                player_id: Some(player_id),
+            },
+            Issue::PotMismatch { contributed, pots } => Diagnostic::InconsistentPot {
+                game_number,
+                expected: contributed,
+                actual: pots,
+            },
+            Issue::Overflow => Diagnostic::AmountOverflow { game_number },
+            issue => Diagnostic::InvalidHand { game_number, issue },
+        }
+    }
+}
+
+impl From<FrameError> for Diagnostic {
+    fn from(e: FrameError) -> Self {
+        Diagnostic::ParseError {
+            index: e.index,
+            offset: e.offset,
+            line: e.line,
+            column: e.column,
+            message: e.message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::ParseError {
+                index,
+                line,
+                column,
+                message,
+                ..
+            } => write!(
+                f,
+                "hand {} skipped: parse error at line {}, column {}: {}",
+                index + 1,
+                line,
+                column,
+                message
+            ),
+            Diagnostic::UnknownAction {
+                game_number,
+                action_number,
+                action,
+            } => write!(
+                f,
+                "hand {}: unknown action '{}' (action {}) was left out",
+                game_number, action, action_number
+            ),
+            Diagnostic::InconsistentPot {
+                game_number,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "hand {}: bets add up to {} but the pots total {}",
+                game_number, expected, actual
+            ),
+            Diagnostic::AmountOverflow { game_number } => {
+                write!(f, "hand {}: amounts too large to reconcile the pot", game_number)
+            }
+            Diagnostic::MissingPlayerId {
+                game_number,
+                action_number,
+                player_id,
+            } => {
+                let what = match action_number {
+                    Some(n) => format!("action {}", n),
+                    None => "a pot winner".to_string(),
+                };
+                match player_id {
+                    Some(id) => write!(
+                        f,
+                        "hand {}: {} refers to unknown player id {}",
+                        game_number, what, id
+                    ),
+                    None => write!(f, "hand {}: {} has no player id", game_number, what),
+                }
+            }
+            Diagnostic::InvalidHand { game_number, issue } => {
+                write!(f, "hand {}: {}", game_number, issue)
+            }
+            Diagnostic::RejectedHand {
+                game_number,
+                issues,
+            } => {
+                let reasons: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                write!(
+                    f,
+                    "hand {} refused in strict mode: {}",
+                    game_number,
+                    reasons.join("; ")
+                )
+            }
+            Diagnostic::HeroNotSeated { game_number, hero } => write!(
+                f,
+                "hand {}: {} is not seated, hole cards are only shown at showdown",
+                game_number, hero
+            ),
+            Diagnostic::DuplicateHand {
+                game_number,
+                dropped,
+                merged,
+            } => {
+                let copies = if *dropped == 1 { "copy" } else { "copies" };
+                write!(f, "hand {}: dropped {} duplicate {}", game_number, dropped, copies)?;
+                match merged.split_last() {
+                    None => Ok(()),
+                    Some((last, [])) => write!(f, ", merging in {}", last),
+                    Some((last, rest)) => {
+                        write!(f, ", merging in {} and {}", rest.join(", "), last)
+                    }
+                }
+            }
+            Diagnostic::UnknownTimeZone { game_number, zone } if zone.is_empty() => write!(
+                f,
+                "hand {}: start time has no time zone, kept as printed",
+                game_number
+            ),
+            Diagnostic::UnknownTimeZone { game_number, zone } => write!(
+                f,
+                "hand {}: unknown time zone {}, start time kept as printed",
+                game_number, zone
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertError {
+    NoHands(Vec<Diagnostic>),
+    Rejected(Vec<Diagnostic>),
+    EmptyOutput,
+    NothingMatched,
+    Input(String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConvertError::NoHands(diagnostics) => {
+                write!(f, "no valid hands could be parsed. please check your file format.")?;
+                if let Some(first) = diagnostics.first() {
+                    write!(f, " first problem: {}", first)?;
+                }
+                Ok(())
+            }
+            ConvertError::Rejected(diagnostics) => {
+                write!(f, "every hand failed validation in strict mode.")?;
+                if let Some(first) = diagnostics.iter().find(|d| d.game_number().is_some()) {
+                    write!(f, " first problem: {}", first)?;
+                }
+                Ok(())
+            }
+            ConvertError::EmptyOutput => {
+                write!(f, "conversion produced no output. check file formatting.")
+            }
+            ConvertError::NothingMatched => write!(f, "no hands match the filter."),
+            ConvertError::Input(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<String> for ConvertError {
+    fn from(message: String) -> Self {
+        ConvertError::Input(message)
+    }
+}
+
+// tauri hands command errors to the frontend as plain strings
+impl Serialize for ConvertError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertReport {
+    pub output: String,
+    pub hands: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub warnings: Vec<String>,
+    // `output` cut up as asked for in the split options, empty without them
+    pub parts: Vec<OutputPart>,
+    // where each hand is in `output`
+    #[serde(skip)]
+    pub spans: Vec<HandSpan>,
+}
+
+impl ConvertReport {
+    pub fn new(output: String, hands: Vec<String>, diagnostics: Vec<Diagnostic>) -> Self {
+        let warnings = diagnostics.iter().map(|d| d.to_string()).collect();
+        ConvertReport {
+            output,
+            hands,
+            diagnostics,
+            warnings,
+            parts: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    // `separator` is what the output has between two hands
+    pub fn with_spans(
+        mut self,
+        spans: Vec<HandSpan>,
+        separator: &str,
+        split: Option<&SplitOptions>,
+    ) -> Self {
+        if let Some(split) = split {
+            self.parts = split_output(&self.output, &spans, separator, split);
+        }
+        self.spans = spans;
+        self
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| matches!(d, Diagnostic::ParseError { .. }))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_messages() {
+        let parse = Diagnostic::from(FrameError {
+            index: 11,
+            offset: 4096,
+            line: 40,
+            column: 7,
+            message: "expected value".to_string(),
+            truncated: false,
+            end: 4200,
+        });
+        assert_eq!(
+            parse.to_string(),
+            "hand 12 skipped: parse error at line 40, column 7: expected value"
+        );
+        assert_eq!(parse.game_number(), None);
+
+        let pot = Diagnostic::InconsistentPot {
+            game_number: "g1".to_string(),
+            expected: Money::from(1.5),
+            actual: Money::from(1.25),
+        };
+        assert_eq!(pot.to_string(), "hand g1: bets add up to 1.5 but the pots total 1.25");
+
+        let json = serde_json::to_value(&pot).unwrap();
+        assert_eq!(json["kind"], "inconsistent_pot");
+        assert_eq!(json["expected"], 1.5);
+    }
+
+    #[test]
+    fn test_report_counts_skipped_hands() {
+        let report = ConvertReport::new(
+            "text".to_string(),
+            vec!["g1".to_string()],
+            vec![
+                Diagnostic::ParseError {
+                    index: 1,
+                    offset: 10,
+                    line: 2,
+                    column: 1,
+                    message: "eof".to_string(),
+                },
+                Diagnostic::AmountOverflow {
+                    game_number: "g1".to_string(),
+                },
+            ],
+        );
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.warnings.len(), 2);
+        assert_eq!(
+            serde_json::to_string(&ConvertError::EmptyOutput).unwrap(),
+            "\"conversion produced no output. check file formatting.\""
+        );
+    }
+}
@@ -0,0 +1,505 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::converter::OhhHand;
+use crate::money::Money;
+
+// filters are expressions over single hands, for example
+//
+//   saw flop and pot > 50bb and not player "Bob"
+//   allin or (date >= 2023-12-05 and date < 2023-12-06)
+//
+// predicates:
+//   saw flop|turn|river   the hero was still in the hand on that street
+//   showdown              two or more players were left at the end
+//   allin                 somebody went all-in
+//   won                   the hero won at least part of a pot
+//   pot OP AMOUNT[bb]     total pot, in money or big blinds
+//   player NAME           someone with that name, display name or id is seated
+//   table NAME            the table name
+//   date OP DATE          start_date_utc, compared at the precision given
+// combined with and/&&, or/||, not/! and parentheses
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Amount {
+    Money(Money),
+    BigBlinds(Money),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Saw(&'static str),
+    Showdown,
+    AllIn,
+    Won,
+    Pot(Op, Amount),
+    Player(String),
+    Table(String),
+    Date(Op, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::And => write!(f, "'and'"),
+            Token::Or => write!(f, "'or'"),
+            Token::Not => write!(f, "'not'"),
+            Token::Op(_) => write!(f, "a comparison"),
+            Token::Word(w) => write!(f, "'{}'", w),
+            Token::Quoted(w) => write!(f, "\"{}\"", w),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(ch) => quoted.push(ch),
+                        None => return Err(format!("unclosed {} in filter", c)),
+                    }
+                }
+                tokens.push(Token::Quoted(quoted));
+            }
+            '<' | '>' | '=' | '!' | '&' | '|' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let (token, pair) = match (c, next) {
+                    ('<', Some('=')) => (Token::Op(Op::Le), true),
+                    ('>', Some('=')) => (Token::Op(Op::Ge), true),
+                    ('!', Some('=')) => (Token::Op(Op::Ne), true),
+                    ('=', Some('=')) => (Token::Op(Op::Eq), true),
+                    ('&', Some('&')) => (Token::And, true),
+                    ('|', Some('|')) => (Token::Or, true),
+                    ('<', _) => (Token::Op(Op::Lt), false),
+                    ('>', _) => (Token::Op(Op::Gt), false),
+                    ('=', _) => (Token::Op(Op::Eq), false),
+                    ('!', _) => (Token::Not, false),
+                    _ => return Err(format!("unexpected '{}' in filter", c)),
+                };
+                if pair {
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "()\"'<>=!&|".contains(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// parentheses and nots nested deeper than this are refused rather than
+// recursed into
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("the filter nests more than {} deep", MAX_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            self.enter()?;
+            let inner = self.not()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                self.enter()?;
+                let inner = self.or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    Some(other) => Err(format!("expected ')' but found {}", other)),
+                    None => Err("missing ')' at the end of the filter".to_string()),
+                }
+            }
+            Some(Token::Word(word)) => self.predicate(&word),
+            Some(other) => Err(format!("expected a condition but found {}", other)),
+            None => Err("the filter ends where a condition was expected".to_string()),
+        }
+    }
+
+    fn value(&mut self, what: &str) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => Ok(w),
+            Some(other) => Err(format!("expected {} but found {}", what, other)),
+            None => Err(format!("expected {} at the end of the filter", what)),
+        }
+    }
+
+    fn op(&mut self, what: &str) -> Result<Op, String> {
+        match self.next() {
+            Some(Token::Op(op)) => Ok(op),
+            _ => Err(format!(
+                "expected a comparison after '{}', like {} > 10",
+                what, what
+            )),
+        }
+    }
+
+    fn predicate(&mut self, word: &str) -> Result<Expr, String> {
+        match word.to_ascii_lowercase().as_str() {
+            "saw" => match self.value("a street")?.to_ascii_lowercase().as_str() {
+                "flop" => Ok(Expr::Saw("Flop")),
+                "turn" => Ok(Expr::Saw("Turn")),
+                "river" => Ok(Expr::Saw("River")),
+                other => Err(format!(
+                    "unknown street '{}', expected flop, turn or river",
+                    other
+                )),
+            },
+            "showdown" => Ok(Expr::Showdown),
+            "allin" | "all-in" => Ok(Expr::AllIn),
+            "won" => Ok(Expr::Won),
+            "pot" => {
+                let op = self.op("pot")?;
+                let value = self.value("an amount")?;
+                let lower = value.to_ascii_lowercase();
+                let amount = match lower.strip_suffix("bb") {
+                    Some(n) => Money::parse(n.trim()).map(Amount::BigBlinds),
+                    None => Money::parse(&lower).map(Amount::Money),
+                };
+                amount
+                    .map(|amount| Expr::Pot(op, amount))
+                    .ok_or_else(|| format!("'{}' is not an amount", value))
+            }
+            "player" | "villain" => Ok(Expr::Player(self.value("a player name")?)),
+            "table" => Ok(Expr::Table(self.value("a table name")?)),
+            "date" => {
+                let op = self.op("date")?;
+                let date = self.value("a date")?;
+                if !date.starts_with(|c: char| c.is_ascii_digit()) {
+                    return Err(format!("'{}' is not a date, expected 2023-12-05", date));
+                }
+                Ok(Expr::Date(op, date))
+            }
+            other => Err(format!("unknown condition '{}'", other)),
+        }
+    }
+}
+
+fn hero_folded_before(h: &OhhHand, street: &str) -> bool {
+    let Some(hero) = h.hero_player_id.as_deref() else {
+        return true;
+    };
+    for round in &h.rounds {
+        if round.street == street {
+            return false;
+        }
+        let folded = round
+            .actions
+            .iter()
+            .any(|a| a.action == "Fold" && a.player_id.as_deref() == Some(hero));
+        if folded {
+            return true;
+        }
+    }
+    // the street was never dealt
+    true
+}
+
+fn went_to_showdown(h: &OhhHand) -> bool {
+    let actions = h.rounds.iter().flat_map(|r| &r.actions);
+    let mut live: Vec<&str> = Vec::new();
+    let mut folded: Vec<&str> = Vec::new();
+    for a in actions {
+        if let Some(pid) = a.player_id.as_deref() {
+            if a.action == "Fold" {
+                folded.push(pid);
+            } else if !live.contains(&pid) {
+                live.push(pid);
+            }
+        }
+    }
+    live.iter().filter(|pid| !folded.contains(pid)).count() >= 2
+}
+
+impl Expr {
+    fn matches(&self, h: &OhhHand) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(h) && b.matches(h),
+            Expr::Or(a, b) => a.matches(h) || b.matches(h),
+            Expr::Not(a) => !a.matches(h),
+            Expr::Saw(street) => !hero_folded_before(h, street),
+            Expr::Showdown => went_to_showdown(h),
+            Expr::AllIn => h
+                .rounds
+                .iter()
+                .flat_map(|r| &r.actions)
+                .any(|a| a.is_allin.unwrap_or(false)),
+            Expr::Won => h.hero_player_id.as_deref().is_some_and(|hero| {
+                h.pots
+                    .iter()
+                    .flat_map(|p| &p.player_wins)
+                    .any(|w| w.player_id == hero && w.win_amount.is_positive())
+            }),
+            Expr::Pot(op, amount) => {
+                let pot: Money = h.pots.iter().map(|p| p.amount).sum();
+                let ordering = match amount {
+                    Amount::Money(m) => pot.cmp(m),
+                    // pot / bb against n, without dividing
+                    Amount::BigBlinds(n) => {
+                        let pot = pot.raw() as i128 * Money::SCALE as i128;
+                        let limit = n.raw() as i128 * h.big_blind_amount.raw() as i128;
+                        pot.cmp(&limit)
+                    }
+                };
+                op.test(ordering)
+            }
+            Expr::Player(name) => h.players.iter().any(|p| {
+                p.name.eq_ignore_ascii_case(name)
+                    || p.display
+                        .as_deref()
+                        .is_some_and(|d| d.eq_ignore_ascii_case(name))
+                    || p.id == *name
+            }),
+            Expr::Table(name) => h.table_name.eq_ignore_ascii_case(name),
+            Expr::Date(op, date) => {
+                let start = h
+                    .start_date_utc
+                    .get(..date.len())
+                    .unwrap_or(&h.start_date_utc);
+                op.test(start.cmp(date.as_str()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Filter, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.next() {
+            None => Ok(Filter { expr }),
+            Some(extra) => Err(format!("unexpected {} in filter", extra)),
+        }
+    }
+
+    pub fn matches(&self, h: &OhhHand) -> bool {
+        self.expr.matches(h)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Filter::parse(text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FilterCount {
+    pub total: usize,
+    pub matched: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PLAYED;
+
+    // bob is the hero, shown as bobby, and his flop bet is marked all in
+    fn hand() -> OhhHand {
+        let mut h = crate::fixtures::hand(PLAYED, "f1");
+        h.hero_player_id = Some("2".to_string());
+        h.players[1].display = Some("bobby".to_string());
+        h.rounds[1].actions[0].is_allin = Some(true);
+        h
+    }
+
+    fn check(filter: &str) -> bool {
+        Filter::parse(filter).unwrap().matches(&hand())
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert!(check("saw flop"));
+        assert!(!check("saw turn"));
+        assert!(!check("showdown"));
+        assert!(check("allin"));
+        assert!(check("won"));
+        assert!(check("pot > 5bb"));
+        assert!(!check("pot > 6bb"));
+        assert!(check("pot >= 6BB"));
+        assert!(check("pot = 0.6"));
+        assert!(check("player bobby") && check("villain \"Bob\"") && !check("player Cy"));
+        assert!(check("table alcyone"));
+        assert!(check("date >= 2023-12-05 && date < 2023-12-06"));
+        assert!(!check("date > 2023-12-05"));
+        assert!(check("date = 2023-12"));
+    }
+
+    #[test]
+    fn test_boolean_logic() {
+        assert!(check("saw turn or allin"));
+        assert!(!check("saw flop and not won"));
+        assert!(check("!(player Cy || showdown) && pot < 100bb"));
+        // and binds tighter than or
+        assert!(check("allin or saw turn and showdown"));
+        assert!(!check("(allin or saw turn) and showdown"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Filter::parse("pot 50bb")
+            .unwrap_err()
+            .contains("comparison"));
+        assert!(Filter::parse("saw preflop")
+            .unwrap_err()
+            .contains("unknown street"));
+        assert!(Filter::parse("(allin").unwrap_err().contains("missing ')'"));
+        assert!(Filter::parse("allin won")
+            .unwrap_err()
+            .contains("unexpected 'won'"));
+        assert!(Filter::parse("bounty > 3")
+            .unwrap_err()
+            .contains("unknown condition"));
+        assert!(Filter::parse("player \"Bob").is_err());
+        assert!(Filter::parse("").is_err());
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |open: &str, close: &str, n: usize| {
+            format!("{}allin{}", open.repeat(n), close.repeat(n))
+        };
+        assert!(check(&nested("(", ")", MAX_DEPTH)));
+        assert!(check(&nested("not not ", "", MAX_DEPTH / 2)));
+        let deep = [
+            nested("(", ")", 100_000),
+            nested("(", "", 100_000),
+            nested("not ", "", 100_000),
+            nested("!", "", 100_000),
+        ];
+        for deep in deep {
+            assert!(Filter::parse(&deep).unwrap_err().contains("nests more than 64"));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// hands and folders shared by the module tests: ann and bob heads up at
+// $0.05/$0.10, with "GAME" standing in for the game number
+
+use crate::converter::{OhhFile, OhhHand};
+use std::fs;
+use std::path::PathBuf;
+
+// ann posts the small blind and folds to bob's big blind
+pub const FOLDED: &str = r#"{"ohh":{"game_number":"GAME","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"Alcyone","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Ann","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.15,"rake":0,"player_wins":[{"player_id":2,"win_amount":0.15}]}]}}"#;
+
+// ann raises, bob calls and takes the pot with a bet on the flop
+pub const PLAYED: &str = r#"{"ohh":{"game_number":"GAME","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"Alcyone","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"Ann","starting_stack":10},{"id":2,"seat":2,"name":"Bob","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Raise","amount":0.25},{"action_number":3,"player_id":2,"action":"Call","amount":0.2}]},{"id":1,"street":"Flop","cards":["Ah","Kd","2c"],"actions":[{"action_number":0,"player_id":2,"action":"Bet","amount":0.5},{"action_number":1,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.6,"rake":0.02,"player_wins":[{"player_id":2,"win_amount":0.58}]}]}}"#;
+
+// the ohh file text of one of the hands above
+pub fn hand_json(hand: &str, game: &str) -> String {
+    hand.replace("GAME", game)
+}
+
+pub fn hand(hand: &str, game: &str) -> OhhHand {
+    serde_json::from_str::<OhhFile>(&hand_json(hand, game))
+        .unwrap()
+        .ohh
+}
+
+// an empty folder under the temp dir, private to this test process
+pub fn scratch_dir(module: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pab-{}-{}-{}", module, name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
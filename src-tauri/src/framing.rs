@@ -0,0 +1,291 @@
+use crate::converter::{OhhFile, OhhHand};
+use log::debug;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct FramedHand {
+    pub index: usize,
+    // where the hand starts, inside its array if it came in one
+    pub offset: usize,
+    // where the record holding it ends, so reading can resume there
+    pub end: usize,
+    pub hand: OhhHand,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameError {
+    pub index: usize,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub truncated: bool,
+    // where reading resumes after the bad record
+    pub end: usize,
+}
+
+pub fn hand_from_value(value: Value) -> Result<OhhHand, String> {
+    if value.get("ohh").is_some() {
+        serde_json::from_value::<OhhFile>(value)
+            .map(|f| f.ohh)
+            .map_err(|e| e.to_string())
+    } else {
+        serde_json::from_value::<OhhHand>(value).map_err(|e| e.to_string())
+    }
+}
+
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+fn skip_whitespace(text: &str, mut pos: usize) -> usize {
+    let bytes = text.as_bytes();
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+// byte offset of a serde_json (line, column) position inside `text[base..]`
+fn absolute_offset(text: &str, base: usize, line: usize, column: usize) -> usize {
+    let mut offset = base;
+    for _ in 1..line {
+        match text[offset..].find('\n') {
+            Some(i) => offset += i + 1,
+            None => return text.len(),
+        }
+    }
+    (offset + column.saturating_sub(1)).min(text.len())
+}
+
+// the elements of the already parsed array at `text[start..end]`, each with
+// its byte offset
+fn array_elements(text: &str, start: usize, end: usize) -> Vec<(usize, Value)> {
+    let mut elements = Vec::new();
+    let mut pos = skip_whitespace(text, start + 1);
+    while pos < end && text.as_bytes()[pos] != b']' {
+        let mut stream = serde_json::Deserializer::from_str(&text[pos..end]).into_iter::<Value>();
+        let Some(Ok(value)) = stream.next() else {
+            break;
+        };
+        elements.push((pos, value));
+        pos = skip_whitespace(text, pos + stream.byte_offset());
+        if text.as_bytes().get(pos) == Some(&b',') {
+            pos = skip_whitespace(text, pos + 1);
+        }
+    }
+    elements
+}
+
+// where to resume after a record that is not valid json: the next line that
+// opens a top-level object or array, or the next `{"ohh"` past the error when
+// that comes sooner, as it does when records are concatenated on one line.
+// any `{` past the error is the last resort.
+fn next_record_start(text: &str, from: usize, error_at: usize) -> Option<usize> {
+    let mut line = None;
+    let mut search = from;
+    while let Some(i) = text[search..].find('\n') {
+        let line_start = search + i + 1;
+        match text.as_bytes().get(line_start) {
+            Some(b'{') | Some(b'[') => {
+                line = Some(line_start);
+                break;
+            }
+            Some(_) => search = line_start,
+            None => break,
+        }
+    }
+
+    let after = error_at.max(from + 1).min(text.len());
+    let wrapped = text[after..].find("{\"ohh\"").map(|i| after + i);
+    match (line, wrapped) {
+        (Some(line), Some(wrapped)) => Some(line.min(wrapped)),
+        (Some(next), None) | (None, Some(next)) => Some(next),
+        (None, None) => text[after..].find('{').map(|i| after + i),
+    }
+}
+
+// splits OHH exports into hands: concatenated objects, JSON Lines, blank-line
+// separated dumps, pretty-printed objects and top-level arrays all frame the
+// same way. every bad record is reported with its byte offset.
+pub fn frame_ohh(text: &str) -> Vec<Result<FramedHand, FrameError>> {
+    let mut out = Vec::new();
+    let mut index = 0;
+    let mut pos = if text.starts_with('\u{feff}') { 3 } else { 0 };
+
+    let error = |index: usize, offset: usize, at: usize, message: String, truncated: bool| {
+        let (line, column) = line_column(text, at);
+        FrameError {
+            index,
+            offset,
+            line,
+            column,
+            message,
+            truncated,
+            end: text.len(),
+        }
+    };
+
+    'outer: loop {
+        pos = skip_whitespace(text, pos);
+        if pos >= text.len() {
+            break;
+        }
+
+        let mut stream = serde_json::Deserializer::from_str(&text[pos..]).into_iter::<Value>();
+        let mut start = pos;
+
+        while let Some(item) = stream.next() {
+            let end = pos + stream.byte_offset();
+            match item {
+                Ok(Value::Array(_)) => {
+                    for (offset, value) in array_elements(text, start, end) {
+                        out.push(
+                            hand_from_value(value)
+                                .map(|hand| FramedHand {
+                                    index,
+                                    offset,
+                                    end,
+                                    hand,
+                                })
+                                .map_err(|e| FrameError {
+                                    end,
+                                    ..error(index, offset, offset, e, false)
+                                }),
+                        );
+                        index += 1;
+                    }
+                }
+                Ok(value) => {
+                    out.push(
+                        hand_from_value(value)
+                            .map(|hand| FramedHand {
+                                index,
+                                offset: start,
+                                end,
+                                hand,
+                            })
+                            .map_err(|e| FrameError {
+                                end,
+                                ..error(index, start, start, e, false)
+                            }),
+                    );
+                    index += 1;
+                }
+                Err(e) => {
+                    // serde reports positions relative to where this stream started
+                    let at = absolute_offset(text, pos, e.line(), e.column());
+                    let message = e.to_string();
+                    let message = match message.rfind(" at line ") {
+                        Some(i) => message[..i].to_string(),
+                        None => message,
+                    };
+                    debug!("record {} at byte {} is not valid json: {}", index, start, message);
+                    let next = next_record_start(text, start, at).filter(|&next| next > start);
+                    let end = next.unwrap_or(text.len());
+                    out.push(Err(FrameError {
+                        end,
+                        ..error(index, start, at, message, e.is_eof())
+                    }));
+                    index += 1;
+
+                    match next {
+                        Some(next) => {
+                            pos = next;
+                            continue 'outer;
+                        }
+                        None => break 'outer,
+                    }
+                }
+            }
+            start = skip_whitespace(text, end);
+        }
+
+        break;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = r#"{"ohh":{"game_number":"g1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"players":[],"rounds":[],"pots":[]}}"#;
+
+    fn hands(text: &str) -> Vec<String> {
+        frame_ohh(text)
+            .into_iter()
+            .filter_map(|r| r.ok())
+            .map(|f| f.hand.game_number)
+            .collect()
+    }
+
+    #[test]
+    fn test_frames_common_layouts() {
+        let jsonl = format!("{}\n{}\n", HAND, HAND.replace("g1", "g2"));
+        assert_eq!(hands(&jsonl), vec!["g1", "g2"]);
+
+        let crlf = format!("\u{feff}{}\r\n\r\n{}\r\n", HAND, HAND.replace("g1", "g2"));
+        assert_eq!(hands(&crlf), vec!["g1", "g2"]);
+
+        let concatenated = format!("{}{}", HAND, HAND.replace("g1", "g2"));
+        assert_eq!(hands(&concatenated), vec!["g1", "g2"]);
+
+        let array = format!("[{}, {}]", HAND, HAND.replace("g1", "g2"));
+        assert_eq!(hands(&array), vec!["g1", "g2"]);
+
+        let pretty = serde_json::to_string_pretty(&serde_json::from_str::<Value>(HAND).unwrap())
+            .unwrap()
+            .replace("\"players\"", "\n\n\"players\"");
+        assert_eq!(hands(&format!("{}\n\n{}", pretty, pretty)), vec!["g1", "g1"]);
+    }
+
+    #[test]
+    fn test_reports_bad_records_with_offsets() {
+        let text = format!("{}\n{{\"ohh\": {{broken\n{}\n{{\"ohh\":{{}}}}\n", HAND, HAND.replace("g1", "g3"));
+        let framed = frame_ohh(&text);
+
+        let errors: Vec<&FrameError> = framed.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(hands(&text), vec!["g1", "g3"]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].offset, HAND.len() + 1);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[0].column, 10);
+        assert_eq!(errors[0].message, "key must be a string");
+        assert!(!errors[1].truncated);
+
+        // hands inside an array are placed on their own lines
+        let array = format!(
+            "[\n  {},\n  {{\"ohh\":{{}}}} ,\n  {}\n]\n",
+            HAND,
+            HAND.replace("g1", "g3")
+        );
+        let framed = frame_ohh(&array);
+        assert_eq!(framed.len(), 3);
+        let error = framed[1].as_ref().unwrap_err();
+        assert_eq!((error.index, error.line, error.column), (1, 3, 3));
+        assert_eq!(error.offset, array.find("{\"ohh\":{}}").unwrap());
+        let last = framed[2].as_ref().unwrap();
+        assert_eq!(last.offset, array.rfind("{\"ohh\"").unwrap());
+        assert_eq!(last.end, array.len() - 1);
+
+        // one bad record on a single line does not hide the ones after it
+        let inline = format!("{}{{\"ohh\":{{broken{}", HAND, HAND.replace("g1", "g3"));
+        let framed = frame_ohh(&inline);
+        assert_eq!(hands(&inline), vec!["g1", "g3"]);
+        assert_eq!(framed.len(), 3);
+        assert_eq!(framed[1].as_ref().unwrap_err().offset, HAND.len());
+        assert_eq!(framed[1].as_ref().unwrap_err().end, framed[2].as_ref().unwrap().offset);
+        let bare = &HAND[7..HAND.len() - 1];
+        let inline = format!("{}{{broken{}", bare, bare.replace("g1", "g3"));
+        assert_eq!(hands(&inline), vec!["g1", "g3"]);
+
+        let truncated = &HAND[..HAND.len() - 10];
+        let framed = frame_ohh(&format!("{}\n{}", HAND, truncated));
+        assert!(framed[1].as_ref().unwrap_err().truncated);
+    }
+}
@@ -0,0 +1,287 @@
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    Trips,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandValue {
+    pub category: Category,
+    pub ranks: Vec<u8>,
+}
+
+impl Ord for HandValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category
+            .cmp(&other.category)
+            .then_with(|| self.ranks.cmp(&other.ranks))
+    }
+}
+
+impl PartialOrd for HandValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn parse_card(c: &str) -> Option<(u8, char)> {
+    let mut chars = c.chars();
+    let rank = match chars.next()?.to_ascii_uppercase() {
+        'A' => 14,
+        'K' => 13,
+        'Q' => 12,
+        'J' => 11,
+        'T' => 10,
+        d @ '2'..='9' => d as u8 - b'0',
+        _ => return None,
+    };
+    let suit = chars.next()?.to_ascii_lowercase();
+    Some((rank, suit))
+}
+
+fn rank_name(rank: u8) -> &'static str {
+    match rank {
+        14 => "Ace",
+        13 => "King",
+        12 => "Queen",
+        11 => "Jack",
+        10 => "Ten",
+        9 => "Nine",
+        8 => "Eight",
+        7 => "Seven",
+        6 => "Six",
+        5 => "Five",
+        4 => "Four",
+        3 => "Three",
+        _ => "Deuce",
+    }
+}
+
+fn rank_plural(rank: u8) -> String {
+    match rank {
+        6 => "Sixes".to_string(),
+        r => format!("{}s", rank_name(r)),
+    }
+}
+
+fn straight_high(ranks: &[u8]) -> Option<u8> {
+    let mut present = [false; 15];
+    for &r in ranks {
+        present[r as usize] = true;
+        if r == 14 {
+            present[1] = true;
+        }
+    }
+    (5..=14u8)
+        .rev()
+        .find(|&high| (high - 4..=high).all(|r| present[r as usize]))
+}
+
+fn evaluate_five(cards: &[(u8, char)]) -> HandValue {
+    let mut counts: Vec<(u8, u8)> = Vec::new();
+    for &(rank, _) in cards {
+        match counts.iter_mut().find(|(r, _)| *r == rank) {
+            Some(entry) => entry.1 += 1,
+            None => counts.push((rank, 1)),
+        }
+    }
+    // most copies first, then highest rank
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+    let ranks: Vec<u8> = counts.iter().map(|(r, _)| *r).collect();
+
+    let is_flush = cards.len() == 5 && cards.iter().all(|(_, s)| *s == cards[0].1);
+    let straight = if cards.len() == 5 && counts.len() == 5 {
+        straight_high(&ranks)
+    } else {
+        None
+    };
+
+    let category = match (straight, is_flush, counts[0].1, counts.get(1).map(|c| c.1)) {
+        (Some(_), true, _, _) => Category::StraightFlush,
+        (_, _, 4, _) => Category::Quads,
+        (_, _, 3, Some(2)) => Category::FullHouse,
+        (_, true, _, _) => Category::Flush,
+        (Some(_), _, _, _) => Category::Straight,
+        (_, _, 3, _) => Category::Trips,
+        (_, _, 2, Some(2)) => Category::TwoPair,
+        (_, _, 2, _) => Category::Pair,
+        _ => Category::HighCard,
+    };
+
+    let ranks = match straight {
+        Some(high) if matches!(category, Category::Straight | Category::StraightFlush) => {
+            vec![high]
+        }
+        _ => ranks,
+    };
+
+    HandValue { category, ranks }
+}
+
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut out = Vec::new();
+    let mut idx: Vec<usize> = (0..k).collect();
+    if k > n {
+        return out;
+    }
+    loop {
+        out.push(idx.clone());
+        let mut i = k;
+        while i > 0 && idx[i - 1] == n - k + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return out;
+        }
+        idx[i - 1] += 1;
+        for j in i..k {
+            idx[j] = idx[j - 1] + 1;
+        }
+    }
+}
+
+pub fn best_hand(cards: &[String]) -> Option<HandValue> {
+    let parsed: Vec<(u8, char)> = cards.iter().filter_map(|c| parse_card(c)).collect();
+    if parsed.is_empty() {
+        return None;
+    }
+    if parsed.len() <= 5 {
+        return Some(evaluate_five(&parsed));
+    }
+
+    combinations(parsed.len(), 5)
+        .iter()
+        .map(|combo| {
+            let five: Vec<(u8, char)> = combo.iter().map(|&i| parsed[i]).collect();
+            evaluate_five(&five)
+        })
+        .max()
+}
+
+pub fn describe(value: &HandValue) -> String {
+    let r = &value.ranks;
+    match value.category {
+        Category::HighCard => format!("high card {}", rank_name(r[0])),
+        Category::Pair => format!("a pair of {}", rank_plural(r[0])),
+        Category::TwoPair => format!("two pair, {} and {}", rank_plural(r[0]), rank_plural(r[1])),
+        Category::Trips => format!("three of a kind, {}", rank_plural(r[0])),
+        Category::Straight => format!(
+            "a straight, {} to {}",
+            rank_name(if r[0] == 5 { 14 } else { r[0] - 4 }),
+            rank_name(r[0])
+        ),
+        Category::Flush => format!("a flush, {} high", rank_name(r[0])),
+        Category::FullHouse => format!(
+            "a full house, {} full of {}",
+            rank_plural(r[0]),
+            rank_plural(r[1])
+        ),
+        Category::Quads => format!("four of a kind, {}", rank_plural(r[0])),
+        Category::StraightFlush if r[0] == 14 => "a Royal Flush".to_string(),
+        Category::StraightFlush => format!(
+            "a straight flush, {} to {}",
+            rank_name(if r[0] == 5 { 14 } else { r[0] - 4 }),
+            rank_name(r[0])
+        ),
+    }
+}
+
+pub fn describe_cards(hole: &[String], board: &[String]) -> Option<String> {
+    let mut all = hole.to_vec();
+    all.extend(board.iter().cloned());
+    best_hand(&all).map(|v| describe(&v))
+}
+
+// omaha hands must play exactly two hole cards and three from the board
+pub fn best_omaha_hand(hole: &[String], board: &[String]) -> Option<HandValue> {
+    let hole: Vec<(u8, char)> = hole.iter().filter_map(|c| parse_card(c)).collect();
+    let board: Vec<(u8, char)> = board.iter().filter_map(|c| parse_card(c)).collect();
+
+    let mut best: Option<HandValue> = None;
+    for h in combinations(hole.len(), 2) {
+        for b in combinations(board.len(), 3) {
+            let five: Vec<(u8, char)> = h
+                .iter()
+                .map(|&i| hole[i])
+                .chain(b.iter().map(|&i| board[i]))
+                .collect();
+            let value = evaluate_five(&five);
+            if best.as_ref().is_none_or(|v| value > *v) {
+                best = Some(value);
+            }
+        }
+    }
+    best
+}
+
+pub fn describe_omaha(hole: &[String], board: &[String]) -> Option<String> {
+    best_omaha_hand(hole, board).map(|v| describe(&v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_describe_made_hands() {
+        assert_eq!(
+            describe_cards(&hand("Ks 2c"), &hand("4d 3c Kd Tc Js")).unwrap(),
+            "a pair of Kings"
+        );
+        assert_eq!(
+            describe_cards(&hand("8s Ac"), &hand("4d 3c Kd Tc Js")).unwrap(),
+            "high card Ace"
+        );
+        assert_eq!(
+            describe_cards(&hand("6h 6d"), &hand("6s Kd Kc 2h 3h")).unwrap(),
+            "a full house, Sixes full of Kings"
+        );
+        assert_eq!(
+            describe_cards(&hand("Ah 2d"), &hand("3s 4d 5c Kh Kd")).unwrap(),
+            "a straight, Ace to Five"
+        );
+        assert_eq!(
+            describe_cards(&hand("Ah Kh"), &hand("Qh Jh Th 2c 3d")).unwrap(),
+            "a Royal Flush"
+        );
+        assert_eq!(
+            describe_cards(&hand("9c 8c"), &hand("2c 3c 4d Kc Kh")).unwrap(),
+            "a flush, King high"
+        );
+    }
+
+    #[test]
+    fn test_omaha_uses_two_hole_cards() {
+        // four hearts on board do not make a flush with a single heart in hand
+        assert_eq!(
+            describe_omaha(&hand("Ah Kd Qc 2s"), &hand("3h 7h 9h Jh 4c")).unwrap(),
+            "high card Ace"
+        );
+        assert_eq!(
+            describe_omaha(&hand("Ah Kh Qc 2s"), &hand("3h 7h 9h Jd 4c")).unwrap(),
+            "a flush, Ace high"
+        );
+    }
+
+    #[test]
+    fn test_best_hand_ordering() {
+        let two_pair = best_hand(&hand("Ks Kd 4c 4h 9s 2d 3c")).unwrap();
+        let trips = best_hand(&hand("7s 7d 7c Ah 9s 2d 3c")).unwrap();
+        assert_eq!(two_pair.category, Category::TwoPair);
+        assert_eq!(two_pair.ranks, vec![13, 4, 9]);
+        assert!(trips > two_pair);
+    }
+}
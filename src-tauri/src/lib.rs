@@ -1,7 +1,37 @@
+pub mod anonymize;
+pub mod batch;
 pub mod converter;
+pub mod dedupe;
+pub mod diagnostics;
+pub mod filter;
+#[cfg(test)]
+mod fixtures;
+pub mod framing;
+pub mod hand_rank;
+pub mod money;
+pub mod naming;
+pub mod pokerstars;
+pub mod split;
+pub mod validate;
+pub mod watch;
+pub mod writer;
 
-use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
 use log::{debug, info, warn, error};
+use tauri::Emitter;
+
+use batch::{BatchOptions, BatchSummary};
+use converter::ConvertOptions;
+use diagnostics::{ConvertError, ConvertReport};
+use filter::FilterCount;
+use naming::Aliases;
+use split::ZipEntry;
+use watch::DirWatcher;
+
+// the folder watch that is running, if any
+#[derive(Default)]
+struct WatchState(Mutex<Option<DirWatcher>>);
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -9,83 +39,86 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-fn convert_ohh_content(content: String) -> Result<String, String> {
+fn convert_ohh_content(
+    content: String,
+    options: Option<ConvertOptions>,
+) -> Result<ConvertReport, ConvertError> {
     debug!("convert_ohh_content called with {} bytes", content.len());
 
-    match converter::convert_ohh_file(&content) {
-        Ok(result) => Ok(result),
+    match converter::convert_ohh_file_with(&content, &options.unwrap_or_default()) {
+        Ok(report) => {
+            for warning in &report.warnings {
+                warn!("{}", warning);
+            }
+            Ok(report)
+        }
         Err(e) => {
             error!("conversion failed: {}", e);
-            Err(format!("conversion failed: {}", e))
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-fn convert_ohh_file_path(file_path: String) -> Result<String, String> {
-    use std::path::Path;
+fn normalize_ohh_content(
+    content: String,
+    options: Option<ConvertOptions>,
+) -> Result<ConvertReport, ConvertError> {
+    debug!("normalize_ohh_content called with {} bytes", content.len());
 
-    debug!("convert_ohh_file_path called with: {}", file_path);
-
-    // Validate file path
-    let path = Path::new(&file_path);
-
-    // Ensure the path is absolute and doesn't contain directory traversal
-    let canonical = path
-        .canonicalize()
-        .map_err(|e| {
-            error!("Failed to canonicalize path: {}", e);
-            "Invalid file path or file does not exist".to_string()
-        })?;
-
-    debug!("Canonical path: {:?}", canonical);
-
-    // Verify file extension
-    if let Some(ext) = canonical.extension() {
-        let ext_str = ext.to_str().unwrap_or("");
-        debug!("File extension: {}", ext_str);
-        if !matches!(ext_str, "ohh" | "txt" | "json") {
-            let err = "Invalid file type. Only .ohh, .txt, or .json files are supported";
-            error!("{}", err);
-            return Err(err.to_string());
+    match converter::normalize_ohh_file_with(&content, &options.unwrap_or_default()) {
+        Ok(report) => {
+            for warning in &report.warnings {
+                warn!("{}", warning);
+            }
+            Ok(report)
+        }
+        Err(e) => {
+            error!("ohh normalization failed: {}", e);
+            Err(e)
         }
-    } else {
-        let err = "File must have an extension";
-        error!("{}", err);
-        return Err(err.to_string());
     }
+}
 
-    // Check file size before reading (prevent DoS)
-    let metadata =
-        fs::metadata(&canonical).map_err(|e| {
-            error!("Failed to get file metadata: {}", e);
-            "Cannot access file".to_string()
-        })?;
-
-    debug!("File size: {} bytes", metadata.len());
+#[tauri::command]
+fn convert_pokerstars_content(
+    content: String,
+    options: Option<ConvertOptions>,
+) -> Result<ConvertReport, ConvertError> {
+    debug!("convert_pokerstars_content called with {} bytes", content.len());
 
-    const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100MB
-    if metadata.len() > MAX_FILE_SIZE {
-        let err = format!(
-            "File too large: {} MB (maximum 100 MB)",
-            metadata.len() / 1024 / 1024
-        );
-        warn!("{}", err);
-        return Err(err);
+    match pokerstars::pokerstars_to_ohh_with(&content, &options.unwrap_or_default()) {
+        Ok(report) => {
+            for warning in &report.warnings {
+                warn!("{}", warning);
+            }
+            Ok(report)
+        }
+        Err(e) => {
+            error!("pokerstars conversion failed: {}", e);
+            Err(e)
+        }
     }
+}
 
-    debug!("Reading file content");
-    let content =
-        fs::read_to_string(&canonical).map_err(|e| {
-            error!("Failed to read file: {}", e);
-            "Failed to read file".to_string()
-        })?;
+#[tauri::command]
+fn convert_ohh_file_path(
+    file_path: String,
+    options: Option<ConvertOptions>,
+) -> Result<ConvertReport, ConvertError> {
+    debug!("convert_ohh_file_path called with: {}", file_path);
+
+    let content = batch::read_hand_file(Path::new(&file_path))?;
 
     debug!("Read {} bytes, starting conversion", content.len());
-    match converter::convert_ohh_file(&content) {
-        Ok(result) => {
-            info!("File conversion successful, output size: {} bytes", result.len());
-            Ok(result)
+    match converter::convert_ohh_file_with(&content, &options.unwrap_or_default()) {
+        Ok(report) => {
+            info!(
+                "File conversion successful, output size: {} bytes, {} warnings",
+                report.output.len(),
+                report.warnings.len()
+            );
+            Ok(report)
         }
         Err(e) => {
             error!("File conversion failed: {}", e);
@@ -94,6 +127,123 @@ fn convert_ohh_file_path(file_path: String) -> Result<String, String> {
     }
 }
 
+// how many hands of an ohh or pokerstars file the filter in `options` keeps
+#[tauri::command]
+fn count_matches(
+    content: String,
+    options: Option<ConvertOptions>,
+) -> Result<FilterCount, ConvertError> {
+    debug!("count_matches called with {} bytes", content.len());
+
+    let (hands, _) = if pokerstars::is_pokerstars_text(&content) {
+        pokerstars::parse_pokerstars_file(&content)?
+    } else {
+        converter::parse_ohh_chunks(&content)?
+    };
+    let count = converter::count_matches(hands, &options.unwrap_or_default()).map_err(|e| {
+        error!("counting matches failed: {}", e);
+        e
+    })?;
+    info!("{} of {} hands match", count.matched, count.total);
+    Ok(count)
+}
+
+#[tauri::command]
+fn load_aliases(file_path: String) -> Result<Aliases, ConvertError> {
+    debug!("load_aliases called with: {}", file_path);
+
+    let aliases = naming::load_aliases(Path::new(&file_path)).map_err(|e| {
+        error!("failed to load aliases: {}", e);
+        e
+    })?;
+    info!("loaded {} aliases", aliases.len());
+    Ok(aliases)
+}
+
+// the parts of a split conversion as one zip, sent back as raw bytes rather
+// than a json array of numbers
+#[tauri::command]
+fn zip_parts(files: Vec<ZipEntry>) -> Result<tauri::ipc::Response, ConvertError> {
+    debug!("zip_parts called with {} files", files.len());
+
+    let zip = split::zip_files(files.iter().map(|f| (f.name.as_str(), f.output.as_str())))
+        .map_err(|e| {
+            error!("failed to zip parts: {}", e);
+            e
+        })?;
+    info!("zipped {} parts into {} bytes", files.len(), zip.len());
+    Ok(tauri::ipc::Response::new(zip))
+}
+
+// runs off the main thread so the window stays responsive, reporting each
+// finished file as a "batch-progress" event
+#[tauri::command]
+async fn convert_batch(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    options: Option<ConvertOptions>,
+    batch: Option<BatchOptions>,
+) -> Result<BatchSummary, ConvertError> {
+    debug!("convert_batch called with {} paths", paths.len());
+
+    let options = options.unwrap_or_default();
+    let batch = batch.unwrap_or_default();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        batch::convert_batch(&paths, &options, &batch, |progress| {
+            if let Err(e) = app.emit("batch-progress", &progress) {
+                error!("failed to send batch progress: {}", e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    result.map_err(|e| {
+        error!("batch conversion failed: {}", e);
+        e.into()
+    })
+}
+
+#[tauri::command]
+fn start_watch(
+    app: tauri::AppHandle,
+    state: tauri::State<WatchState>,
+    input_dir: String,
+    output_dir: String,
+    options: Option<ConvertOptions>,
+) -> Result<(), ConvertError> {
+    debug!("start_watch called for {} into {}", input_dir, output_dir);
+
+    // stop the previous watch before its cursor is picked up again
+    state.0.lock().unwrap().take();
+
+    let watcher = watch::watch_dir(
+        Path::new(&input_dir),
+        Path::new(&output_dir),
+        options.unwrap_or_default(),
+        move |update| {
+            match &update.error {
+                Some(e) => warn!("{}: {}", update.file, e),
+                None => info!("{}: {} new hands", update.file, update.hands.len()),
+            }
+            if let Err(e) = app.emit("watch-update", &update) {
+                error!("failed to send watch update: {}", e);
+            }
+        },
+    )
+    .inspect_err(|e| error!("failed to start watch: {}", e))?;
+
+    *state.0.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+#[tauri::command]
+fn stop_watch(state: tauri::State<WatchState>) {
+    if state.0.lock().unwrap().take().is_some() {
+        info!("watch stopped");
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize logging to file
@@ -152,10 +302,19 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(WatchState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             convert_ohh_content,
-            convert_ohh_file_path
+            normalize_ohh_content,
+            convert_pokerstars_content,
+            convert_ohh_file_path,
+            convert_batch,
+            count_matches,
+            zip_parts,
+            load_aliases,
+            start_watch,
+            stop_watch
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,362 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+// fixed-point amount in ten-thousandths of a unit, enough for sub-cent
+// antes while keeping every sum exact
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const DECIMALS: u32 = 4;
+    pub const SCALE: i64 = 10_000;
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_raw(raw: i64) -> Self {
+        Money(raw)
+    }
+
+    pub fn from_f64(x: f64) -> Self {
+        Money((x * Self::SCALE as f64).round() as i64)
+    }
+
+    pub fn from_whole(units: i64) -> Self {
+        Money(units.saturating_mul(Self::SCALE))
+    }
+
+    pub fn raw(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn abs(self) -> Self {
+        Money(self.0.saturating_abs())
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    // rounds half away from zero to the given number of decimals and
+    // returns the amount in those units
+    pub fn round_to(self, decimals: u32) -> i64 {
+        if decimals >= Self::DECIMALS {
+            return self.0.saturating_mul(10i64.pow(decimals - Self::DECIMALS));
+        }
+        let step = 10i64.pow(Self::DECIMALS - decimals);
+        let half = step / 2;
+        if self.0 >= 0 {
+            self.0.saturating_add(half) / step
+        } else {
+            self.0.saturating_sub(half) / step
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Money> {
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return None;
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !frac.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+        let mut frac_digits: String = frac.chars().take(Self::DECIMALS as usize + 1).collect();
+        while frac_digits.len() < Self::DECIMALS as usize + 1 {
+            frac_digits.push('0');
+        }
+        // one extra digit so the last kept place is rounded, not truncated
+        let frac: i64 = frac_digits.parse().ok()?;
+        let frac = (frac + 5) / 10;
+
+        let raw = whole.checked_mul(Self::SCALE)?.checked_add(frac)?;
+        Some(Money(if negative { -raw } else { raw }))
+    }
+}
+
+impl From<f64> for Money {
+    fn from(x: f64) -> Self {
+        Money::from_f64(x)
+    }
+}
+
+impl From<i64> for Money {
+    fn from(units: i64) -> Self {
+        Money::from_whole(units)
+    }
+}
+
+// the operators saturate so a hand with absurd amounts cannot panic the
+// writer; `validate` uses the checked versions to report it
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0.saturating_sub(other.0))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.saturating_neg())
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |a, b| a + b)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |a, b| a + *b)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let scale = Self::SCALE as u64;
+        let frac = format!("{:04}", abs % scale);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            write!(f, "{}{}", sign, abs / scale)
+        } else {
+            write!(f, "{}{}.{}", sign, abs / scale, frac)
+        }
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 % Self::SCALE == 0 {
+            serializer.serialize_i64(self.0 / Self::SCALE)
+        } else {
+            serializer.serialize_f64(self.to_f64())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        use serde::de::{self, Visitor};
+
+        struct MoneyVisitor;
+
+        impl<'de> Visitor<'de> for MoneyVisitor {
+            type Value = Money;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number or decimal string amount")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Money, E> {
+                value
+                    .checked_mul(Money::SCALE)
+                    .map(Money)
+                    .ok_or_else(|| E::custom("amount out of range"))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Money, E> {
+                i64::try_from(value)
+                    .ok()
+                    .and_then(|v| v.checked_mul(Money::SCALE))
+                    .map(Money)
+                    .ok_or_else(|| E::custom("amount out of range"))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Money, E> {
+                if !value.is_finite() || value.abs() > (i64::MAX / Money::SCALE) as f64 {
+                    return Err(E::custom("amount out of range"));
+                }
+                Ok(Money::from_f64(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Money, E> {
+                Money::parse(value).ok_or_else(|| E::custom(format!("invalid amount: {}", value)))
+            }
+        }
+
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoneyFormat {
+    pub symbol: String,
+    pub decimals: u32,
+    pub grouping: bool,
+    pub trim_whole: bool,
+}
+
+impl MoneyFormat {
+    pub fn for_currency(code: &str) -> Self {
+        let symbol = match code.to_uppercase().as_str() {
+            "USD" => "$",
+            "EUR" => "€",
+            "GBP" => "£",
+            _ => "",
+        };
+
+        MoneyFormat {
+            symbol: symbol.to_string(),
+            decimals: 2,
+            grouping: !symbol.is_empty(),
+            trim_whole: false,
+        }
+    }
+
+    // tournament chips: no symbol, no grouping and no decimals on whole amounts
+    pub fn chips() -> Self {
+        MoneyFormat {
+            symbol: String::new(),
+            decimals: 2,
+            grouping: false,
+            trim_whole: true,
+        }
+    }
+
+    pub fn format(&self, x: impl Into<Money>) -> String {
+        let units = x.into().round_to(self.decimals);
+        let scale = 10u64.pow(self.decimals);
+        let whole = units.unsigned_abs() / scale;
+        let frac = units.unsigned_abs() % scale;
+
+        let whole_str = if self.grouping {
+            group_thousands(whole)
+        } else {
+            whole.to_string()
+        };
+
+        let number = if self.decimals == 0 || (self.trim_whole && frac == 0) {
+            whole_str
+        } else {
+            format!(
+                "{}.{:0width$}",
+                whole_str,
+                frac,
+                width = self.decimals as usize
+            )
+        };
+
+        let sign = if units < 0 { "-" } else { "" };
+        format!("{}{}{}", sign, self.symbol, number)
+    }
+}
+
+fn group_thousands(n: u64) -> String {
+    let s = n.to_string();
+    let len = s.len();
+    let mut result = String::with_capacity(len + len / 3);
+    for (i, c) in s.chars().enumerate() {
+        if i > 0 && (len - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+pub fn fmt_money(x: impl Into<Money>) -> String {
+    MoneyFormat::for_currency("USD").format(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_symbols() {
+        assert_eq!(MoneyFormat::for_currency("EUR").format(1234.5), "€1,234.50");
+        assert_eq!(MoneyFormat::for_currency("gbp").format(0.1), "£0.10");
+        assert_eq!(MoneyFormat::for_currency("PPC").format(0.05), "0.05");
+        assert_eq!(MoneyFormat::for_currency("PPC").format(1500.0), "1500.00");
+    }
+
+    #[test]
+    fn test_chips_and_precision() {
+        assert_eq!(MoneyFormat::chips().format(1500.0), "1500");
+        assert_eq!(MoneyFormat::chips().format(12.5), "12.50");
+        let usd = |decimals| MoneyFormat {
+            decimals,
+            ..MoneyFormat::for_currency("USD")
+        };
+        assert_eq!(usd(3).format(0.005), "$0.005");
+        assert_eq!(usd(0).format(12.6), "$13");
+    }
+
+    #[test]
+    fn test_money_arithmetic_is_exact() {
+        let parts = [0.1, 0.2, 0.3, 0.15, 0.05];
+        let total: Money = parts.iter().map(|&x| Money::from(x)).sum();
+        assert_eq!(total, Money::from(0.8));
+        assert_eq!(Money::from(0.3) - Money::from(0.1), Money::from(0.2));
+        assert_eq!(Money::from_raw(i64::MAX).checked_add(Money::from_raw(1)), None);
+
+        let huge = Money::from_raw(i64::MAX - 1);
+        assert_eq!(huge + huge, Money::from_raw(i64::MAX));
+        assert_eq!([huge, huge].iter().sum::<Money>(), Money::from_raw(i64::MAX));
+        assert_eq!(-huge - huge, Money::from_raw(i64::MIN));
+    }
+
+    #[test]
+    fn test_parse_and_serde() {
+        assert_eq!(Money::parse("0.995"), Some(Money::from_raw(9950)));
+        assert_eq!(Money::parse("-12.34567"), Some(Money::from_raw(-123457)));
+        assert_eq!(Money::parse("abc"), None);
+
+        let m: Money = serde_json::from_str("\"1.25\"").unwrap();
+        assert_eq!(m, Money::from(1.25));
+        let m: Money = serde_json::from_str("3").unwrap();
+        assert_eq!(serde_json::to_string(&m).unwrap(), "3");
+        let m: Money = serde_json::from_str("0.05").unwrap();
+        assert_eq!(serde_json::to_string(&m).unwrap(), "0.05");
+    }
+}
@@ -0,0 +1,232 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::converter::OhhHand;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NamePolicy {
+    // the site's screen name
+    #[default]
+    Name,
+    // the display name; players without one are shown by seat
+    Display,
+    // the display name where there is one, otherwise the screen name
+    DisplayOrName,
+}
+
+impl NamePolicy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "name" => Some(NamePolicy::Name),
+            "display" => Some(NamePolicy::Display),
+            "display-or-name" | "fallback" => Some(NamePolicy::DisplayOrName),
+            _ => None,
+        }
+    }
+}
+
+// screen or display name to the name to print instead
+pub type Aliases = HashMap<String, String>;
+
+// alias files are a json object or `screen name = alias` lines, with blank
+// lines and lines starting with '#' skipped
+pub fn parse_aliases(text: &str) -> Result<Aliases, String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.starts_with('{') {
+        return serde_json::from_str(text).map_err(|e| format!("invalid alias file: {}", e));
+    }
+
+    let mut aliases = Aliases::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((name, alias)) if !name.trim().is_empty() && !alias.trim().is_empty() => {
+                aliases.insert(name.trim().to_string(), alias.trim().to_string());
+            }
+            _ => {
+                return Err(format!(
+                    "invalid alias file: line {} is not `name = alias`",
+                    index + 1
+                ))
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+pub fn load_aliases(path: &Path) -> Result<Aliases, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    parse_aliases(&text)
+}
+
+// pokerstars parsers take ':' as the end of the name on action lines and
+// parentheses and brackets as stacks, positions and cards
+pub fn escape_name(name: &str) -> String {
+    let escaped: String = name
+        .chars()
+        .map(|c| match c {
+            ':' => ';',
+            '(' | '[' => '{',
+            ')' | ']' => '}',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    escaped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// the name to print for each screen name, escaped and unique across every
+// hand it was built from, so two players who share a display name or alias
+// keep the same printed names in all of them. players met later get a
+// numbered suffix when two names clash
+#[derive(Debug, Clone, Default)]
+pub struct PrintedNames {
+    names: HashMap<String, String>,
+}
+
+impl PrintedNames {
+    pub fn new(hands: &[OhhHand], policy: NamePolicy, aliases: &Aliases) -> Self {
+        let mut names = HashMap::new();
+        let mut taken = HashSet::new();
+        for p in hands.iter().flat_map(|h| &h.players) {
+            if names.contains_key(&p.name) {
+                continue;
+            }
+            let alias = aliases
+                .get(&p.name)
+                .or_else(|| p.display.as_ref().and_then(|d| aliases.get(d)));
+            let display = p.display.clone().filter(|d| !d.trim().is_empty());
+            let chosen = match (alias, policy) {
+                (Some(alias), _) => alias.clone(),
+                (None, NamePolicy::Name) => p.name.clone(),
+                (None, NamePolicy::Display) => display.unwrap_or_default(),
+                (None, NamePolicy::DisplayOrName) => display.unwrap_or_else(|| p.name.clone()),
+            };
+
+            let mut name = escape_name(&chosen);
+            if name.is_empty() {
+                name = format!("Seat{}", p.seat);
+            }
+            let base = name.clone();
+            let mut n = 2;
+            while !taken.insert(name.clone()) {
+                name = format!("{}_{}", base, n);
+                n += 1;
+            }
+            names.insert(p.name.clone(), name);
+        }
+        PrintedNames { names }
+    }
+
+    pub fn apply(&self, h: &mut OhhHand) {
+        for p in &mut h.players {
+            if let Some(name) = self.names.get(&p.name) {
+                p.name = name.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(players: &[(&str, Option<&str>)]) -> OhhHand {
+        let players: Vec<String> = players
+            .iter()
+            .enumerate()
+            .map(|(i, (name, display))| {
+                let display = display
+                    .map(|d| format!(r#","display":"{}""#, d))
+                    .unwrap_or_default();
+                format!(
+                    r#"{{"id":{},"seat":{},"name":"{}"{},"starting_stack":10}}"#,
+                    i + 1,
+                    i + 1,
+                    name,
+                    display
+                )
+            })
+            .collect();
+        let json = format!(
+            r#"{{"game_number":"n1","start_date_utc":"2023-12-05T02:47:13.126Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"players":[{}],"rounds":[],"pots":[]}}"#,
+            players.join(",")
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn apply_names(h: &mut OhhHand, policy: NamePolicy, aliases: &Aliases) {
+        PrintedNames::new(std::slice::from_ref(h), policy, aliases).apply(h);
+    }
+
+    fn names(h: &OhhHand) -> Vec<&str> {
+        h.players.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_policies() {
+        let players = [("CFFl2rCOze", Some("bella")), ("E9V-2MDLwt", None)];
+
+        let mut h = hand(&players);
+        apply_names(&mut h, NamePolicy::Name, &Aliases::new());
+        assert_eq!(names(&h), ["CFFl2rCOze", "E9V-2MDLwt"]);
+
+        let mut h = hand(&players);
+        apply_names(&mut h, NamePolicy::Display, &Aliases::new());
+        assert_eq!(names(&h), ["bella", "Seat2"]);
+
+        let mut h = hand(&players);
+        apply_names(&mut h, NamePolicy::DisplayOrName, &Aliases::new());
+        assert_eq!(names(&h), ["bella", "E9V-2MDLwt"]);
+
+        let aliases =
+            parse_aliases("# study group\nE9V-2MDLwt = Red Orange\n\nbella=Bella\n").unwrap();
+        let mut h = hand(&players);
+        apply_names(&mut h, NamePolicy::Name, &aliases);
+        assert_eq!(names(&h), ["Bella", "Red Orange"]);
+    }
+
+    #[test]
+    fn test_escaped_and_unique() {
+        let mut h = hand(&[
+            ("a", Some("King: of (all)")),
+            ("b", Some("King; of {all}")),
+            ("c", Some("sam")),
+            ("d", Some("sam")),
+        ]);
+        apply_names(&mut h, NamePolicy::Display, &Aliases::new());
+        assert_eq!(
+            names(&h),
+            ["King; of {all}", "King; of {all}_2", "sam", "sam_2"]
+        );
+    }
+
+    #[test]
+    fn test_names_agree_across_hands() {
+        let first = hand(&[("a", Some("sam")), ("b", Some("sam"))]);
+        let second = hand(&[("b", Some("sam")), ("c", None), ("a", Some("sam"))]);
+        let mut hands = vec![first, second];
+        let printed = PrintedNames::new(&hands, NamePolicy::DisplayOrName, &Aliases::new());
+        for h in &mut hands {
+            printed.apply(h);
+        }
+        assert_eq!(names(&hands[0]), ["sam", "sam_2"]);
+        assert_eq!(names(&hands[1]), ["sam_2", "c", "sam"]);
+    }
+
+    #[test]
+    fn test_parse_aliases() {
+        let json = parse_aliases(r#"{"CFFl2rCOze": "bella"}"#).unwrap();
+        assert_eq!(json.get("CFFl2rCOze").map(String::as_str), Some("bella"));
+
+        let err = parse_aliases("bella\n").unwrap_err();
+        assert!(err.contains("line 1"));
+    }
+}
@@ -0,0 +1,918 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use log::{debug, info, warn};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::anonymize::Anonymizer;
+use crate::converter::{
+    live_dead_amount, normalize_prepared_hands, prepare_hands, Action, BetLimit, ConvertOptions,
+    Extra, OhhHand, Player, PlayerWin, Pot, Round, TournamentInfo,
+};
+use crate::diagnostics::{ConvertError, ConvertReport, Diagnostic};
+use crate::money::Money;
+
+struct Patterns {
+    header: Regex,
+    eastern_time: Regex,
+    tournament: Regex,
+    cash: Regex,
+    table: Regex,
+    seat: Regex,
+    street: Regex,
+    dealt: Regex,
+    collected: Regex,
+    total_pot: Regex,
+    side_pot: Regex,
+    summary_muck: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        header: Regex::new(
+            r"^PokerStars (?:[\w ]+ )?Hand #([^:\s]+): (.+?) - (\d{4})[/-](\d{1,2})[/-](\d{1,2}) (\d{1,2}):(\d{2}):(\d{2})(?: (\w+))?",
+        )
+        .unwrap(),
+        eastern_time: Regex::new(
+            r"\[(\d{4})[/-](\d{1,2})[/-](\d{1,2}) (\d{1,2}):(\d{2}):(\d{2}) (\w+)\]",
+        )
+        .unwrap(),
+        tournament: Regex::new(
+            r"^Tournament #([^,]+), (Freeroll|\S+?(?: [A-Z]{3})?) (.+?) (No Limit|Pot Limit|Limit)(?: - Level ([IVXLCDM]+))? \(([^/]+)/([^)]+)\)$",
+        )
+        .unwrap(),
+        cash: Regex::new(
+            r"^(.+?) (No Limit|Pot Limit|Limit) \(([^/]+)/([^) ]+)(?: - Ante ([^) ]+))?(?: ([A-Z]{3}))?\)$",
+        )
+        .unwrap(),
+        table: Regex::new(r"^Table '(.*)' (\d+)-max(?: \(Play Money\))? Seat #(\d+) is the button")
+            .unwrap(),
+        seat: Regex::new(r"^Seat (\d+): (.+) \(([^()]+?) in chips(?:, ([^()]+) bounty)?\)")
+            .unwrap(),
+        street: Regex::new(r"^\*\*\* ([A-Z ]+) \*\*\*(.*)$").unwrap(),
+        dealt: Regex::new(r"^Dealt to (.+?) (?:\[[^\]]*\] )?\[([^\]]+)\]$").unwrap(),
+        collected: Regex::new(r"^(.+) collected (\S+) from (pot|main pot|side pot(?:-(\d+))?)$")
+            .unwrap(),
+        total_pot: Regex::new(r"^Total pot (\S+)(.*?) \| Rake (\S+)").unwrap(),
+        side_pot: Regex::new(r"(Main pot|Side pot(?:-(\d+))?) (\S+?)\.(?: |$)").unwrap(),
+        summary_muck: Regex::new(r"^Seat \d+: (.+?)(?: \([^)]*\))* mucked \[([^\]]+)\]").unwrap(),
+    })
+}
+
+fn parse_amount(s: &str) -> Result<Money, String> {
+    let cleaned: String = s
+        .trim()
+        .trim_start_matches(['$', '€', '£'])
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    Money::parse(&cleaned).ok_or_else(|| format!("invalid amount: {}", s))
+}
+
+fn currency_from_symbol(s: &str) -> Option<String> {
+    match s.trim().chars().next()? {
+        '$' => Some("USD".to_string()),
+        '€' => Some("EUR".to_string()),
+        '£' => Some("GBP".to_string()),
+        _ => None,
+    }
+}
+
+pub fn from_roman(s: &str) -> Option<u32> {
+    let value = |c: char| match c {
+        'I' => Some(1),
+        'V' => Some(5),
+        'X' => Some(10),
+        'L' => Some(50),
+        'C' => Some(100),
+        'D' => Some(500),
+        'M' => Some(1000),
+        _ => None,
+    };
+
+    let digits: Vec<u32> = s.chars().map(value).collect::<Option<Vec<_>>>()?;
+    let mut total = 0;
+    for (i, d) in digits.iter().enumerate() {
+        match digits.get(i + 1) {
+            Some(next) if next > d => total -= *d as i64,
+            _ => total += *d as i64,
+        }
+    }
+    u32::try_from(total).ok().filter(|n| *n > 0)
+}
+
+// the date and time in the six groups from `first` on
+fn local_time(caps: &Captures, first: usize) -> Option<NaiveDateTime> {
+    let n = |i: usize| caps[first + i].parse::<u32>().ok();
+    let date = NaiveDate::from_ymd_opt(caps[first].parse().ok()?, n(1)?, n(2)?)?;
+    date.and_hms_opt(n(3)?, n(4)?, n(5)?)
+}
+
+// us eastern time is on daylight saving from 2am on the second sunday in
+// march to 2am on the first sunday in november; the repeated hour is read as
+// the first of the two
+fn eastern_offset(local: NaiveDateTime) -> Option<i64> {
+    let two = NaiveTime::from_hms_opt(2, 0, 0)?;
+    let start = NaiveDate::from_weekday_of_month_opt(local.year(), 3, Weekday::Sun, 2)?;
+    let end = NaiveDate::from_weekday_of_month_opt(local.year(), 11, Weekday::Sun, 1)?;
+    if local >= start.and_time(two) && local < end.and_time(two) {
+        Some(-4)
+    } else {
+        Some(-5)
+    }
+}
+
+// a time printed in one of the zones pokerstars uses, in utc
+fn to_utc(local: NaiveDateTime, zone: &str) -> Option<NaiveDateTime> {
+    let hours = match zone.to_ascii_uppercase().as_str() {
+        "UTC" | "GMT" => 0,
+        "CET" => 1,
+        "CEST" => 2,
+        "EST" => -5,
+        "EDT" => -4,
+        "ET" => eastern_offset(local)?,
+        _ => return None,
+    };
+    Some(local - Duration::hours(hours))
+}
+
+fn game_type(name: &str) -> String {
+    let name = name.trim_start_matches("5 Card ");
+    match name {
+        "Hold'em" => "Holdem".to_string(),
+        "Omaha Hi/Lo" => "OmahaHiLo".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn bet_type(limit: &str) -> String {
+    match limit {
+        "Pot Limit" => "PL",
+        "Limit" => "FL",
+        _ => "NL",
+    }
+    .to_string()
+}
+
+fn split_cards(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|c| c.to_string()).collect()
+}
+
+// "pot" and "main pot" are pot 0, "side pot" alone is pot 1
+fn pot_number(label: &str, number: Option<regex::Match>) -> usize {
+    if !label.to_lowercase().starts_with("side pot") {
+        return 0;
+    }
+    number.and_then(|n| n.as_str().parse().ok()).unwrap_or(1)
+}
+
+// state for the street currently being read
+struct Street {
+    round: Round,
+    committed: HashMap<String, Money>,
+}
+
+impl Street {
+    fn new(id: u8, street: &str, cards: Vec<String>) -> Self {
+        Street {
+            round: Round {
+                id,
+                street: street.to_string(),
+                cards,
+                actions: Vec::new(),
+                extra: Extra::new(),
+            },
+            committed: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, player_id: &str, action: &str, amount: Option<Money>, allin: bool) {
+        self.round.actions.push(Action {
+            action_number: self.round.actions.len() as u32,
+            player_id: Some(player_id.to_string()),
+            action: action.to_string(),
+            amount,
+            is_allin: Some(allin),
+            cards: None,
+            extra: Extra::new(),
+        });
+    }
+}
+
+// the player whose name starts the action line, longest name first so
+// "Bob: 2" never shadows "Bob"
+fn split_actor<'a>(line: &'a str, names: &'a [(String, String)]) -> Option<(&'a str, &'a str)> {
+    names.iter().find_map(|(name, id)| {
+        line.strip_prefix(name.as_str())
+            .and_then(|rest| rest.strip_prefix(": "))
+            .map(|rest| (id.as_str(), rest))
+    })
+}
+
+pub fn parse_pokerstars_hand(text: &str) -> Result<(OhhHand, Vec<Diagnostic>), String> {
+    let p = patterns();
+    let mut lines = text.lines().map(|l| l.trim_end()).filter(|l| !l.is_empty());
+
+    let header = lines.next().ok_or("empty hand")?;
+    let caps = p
+        .header
+        .captures(header)
+        .ok_or_else(|| format!("not a pokerstars hand header: {}", header))?;
+    let game_number = caps[1].to_string();
+    let description = caps[2].to_string();
+    let mut diagnostics = Vec::new();
+    // the client prints its own zone first, and eastern time in brackets
+    // after it when that is a different zone
+    let printed = local_time(&caps, 3).ok_or_else(|| format!("invalid start time: {}", header))?;
+    let zone = caps.get(9).map_or("", |z| z.as_str());
+    let utc = to_utc(printed, zone).or_else(|| {
+        let e = p.eastern_time.captures(header)?;
+        to_utc(local_time(&e, 1)?, &e[7])
+    });
+    let start_date_utc = match utc {
+        Some(utc) => utc,
+        None => {
+            debug!("hand {}: unknown time zone {:?}", game_number, zone);
+            diagnostics.push(Diagnostic::UnknownTimeZone {
+                game_number: game_number.clone(),
+                zone: zone.to_string(),
+            });
+            printed
+        }
+    }
+    .format("%Y-%m-%dT%H:%M:%SZ")
+    .to_string();
+
+    let mut tournament_info = None;
+    let (game, limit, sb, bb, ante, currency) =
+        if let Some(t) = p.tournament.captures(&description) {
+            let mut info = TournamentInfo {
+                tournament_number: Some(t[1].to_string()),
+                name: None,
+                currency: None,
+                buyin_amount: None,
+                fee_amount: None,
+                bounty_fee_amount: None,
+                initial_stack: None,
+                level: t.get(5).and_then(|l| from_roman(l.as_str())),
+                extra: Extra::new(),
+            };
+
+            let cost = &t[2];
+            if cost == "Freeroll" {
+                info.buyin_amount = Some(Money::ZERO);
+                info.fee_amount = Some(Money::ZERO);
+            } else {
+                let (parts, code) = match cost.rsplit_once(' ') {
+                    Some((parts, code)) => (parts, Some(code.to_string())),
+                    None => (cost, None),
+                };
+                let parts = parts
+                    .split('+')
+                    .map(parse_amount)
+                    .collect::<Result<Vec<_>, _>>()?;
+                match parts.as_slice() {
+                    [buyin, fee] => {
+                        info.buyin_amount = Some(*buyin);
+                        info.fee_amount = Some(*fee);
+                    }
+                    [buyin, bounty, fee] => {
+                        info.buyin_amount = Some(*buyin);
+                        info.bounty_fee_amount = Some(*bounty);
+                        info.fee_amount = Some(*fee);
+                    }
+                    _ => return Err(format!("unrecognized buy-in: {}", cost)),
+                }
+                info.currency = code.or_else(|| currency_from_symbol(cost));
+            }
+            tournament_info = Some(info);
+
+            (
+                t[3].to_string(),
+                t[4].to_string(),
+                parse_amount(&t[6])?,
+                parse_amount(&t[7])?,
+                None,
+                None,
+            )
+        } else if let Some(c) = p.cash.captures(&description) {
+            let ante = match c.get(5) {
+                Some(a) => Some(parse_amount(a.as_str())?),
+                None => None,
+            };
+            let currency = c
+                .get(6)
+                .map(|m| m.as_str().to_string())
+                .or_else(|| currency_from_symbol(&c[3]));
+            (
+                c[1].to_string(),
+                c[2].to_string(),
+                parse_amount(&c[3])?,
+                parse_amount(&c[4])?,
+                ante,
+                currency,
+            )
+        } else {
+            return Err(format!("unrecognized game description: {}", description));
+        };
+
+    let table_line = lines.next().ok_or("missing table line")?;
+    let t = p
+        .table
+        .captures(table_line)
+        .ok_or_else(|| format!("unrecognized table line: {}", table_line))?;
+    let table_name = t[1].to_string();
+    let table_size: u8 = t[2].parse().map_err(|_| "invalid table size")?;
+    let dealer_seat: u8 = t[3].parse().map_err(|_| "invalid button seat")?;
+
+    let mut players: Vec<Player> = Vec::new();
+    let mut rest: Vec<&str> = Vec::new();
+    for line in lines.by_ref() {
+        match p.seat.captures(line) {
+            Some(s) if rest.is_empty() => {
+                let seat: u8 = s[1].parse().map_err(|_| "invalid seat")?;
+                let player_bounty = match s.get(4) {
+                    Some(b) => Some(parse_amount(b.as_str())?),
+                    None => None,
+                };
+                players.push(Player {
+                    id: seat.to_string(),
+                    seat,
+                    name: s[2].to_string(),
+                    display: None,
+                    starting_stack: parse_amount(&s[3])?,
+                    player_bounty,
+                    extra: Extra::new(),
+                });
+            }
+            _ => {
+                rest.push(line);
+                break;
+            }
+        }
+    }
+    rest.extend(lines);
+    if players.is_empty() {
+        return Err("hand has no seated players".to_string());
+    }
+
+    let mut names: Vec<(String, String)> =
+        players.iter().map(|p| (p.name.clone(), p.id.clone())).collect();
+    names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    let id_for = |name: &str| names.iter().find(|(n, _)| n == name).map(|(_, id)| id.clone());
+
+    let mut rounds: Vec<Round> = Vec::new();
+    let mut street = Street::new(0, "Preflop", Vec::new());
+    let mut dealt: Vec<(String, Vec<String>)> = Vec::new();
+    let mut wins: Vec<(usize, PlayerWin)> = Vec::new();
+    let mut total_pot = None;
+    let mut rake = Money::ZERO;
+    let mut side_pots: Vec<(usize, Money)> = Vec::new();
+    let mut summary_mucks: Vec<(String, Vec<String>)> = Vec::new();
+    let mut in_summary = false;
+    let mut posted_sb = false;
+    let mut posted_bb = false;
+
+    for line in rest {
+        if let Some(s) = p.street.captures(line) {
+            let name = s[1].trim();
+            let board: Vec<String> = s[2]
+                .rsplit('[')
+                .next()
+                .map(|c| split_cards(c.trim_end_matches(']')))
+                .unwrap_or_default();
+            let next = match name {
+                "HOLE CARDS" => continue,
+                "FLOP" => "Flop",
+                "TURN" => "Turn",
+                "RIVER" => "River",
+                "SHOW DOWN" => "Showdown",
+                "SUMMARY" => {
+                    in_summary = true;
+                    continue;
+                }
+                other => {
+                    debug!("hand {}: skipping section {}", game_number, other);
+                    continue;
+                }
+            };
+            let board = if next == "Showdown" { Vec::new() } else { board };
+            let id = rounds.len() as u8 + 1;
+            let finished = std::mem::replace(&mut street, Street::new(id, next, board));
+            rounds.push(finished.round);
+            continue;
+        }
+
+        if in_summary {
+            if let Some(t) = p.total_pot.captures(line) {
+                total_pot = Some(parse_amount(&t[1])?);
+                rake = parse_amount(&t[3])?;
+                for sp in p.side_pot.captures_iter(&t[2]) {
+                    side_pots.push((pot_number(&sp[1], sp.get(2)), parse_amount(&sp[3])?));
+                }
+            } else if let Some(m) = p.summary_muck.captures(line) {
+                summary_mucks.push((m[1].to_string(), split_cards(&m[2])));
+            }
+            continue;
+        }
+
+        if let Some(d) = p.dealt.captures(line) {
+            if let Some(id) = id_for(&d[1]) {
+                dealt.push((id, split_cards(&d[2])));
+            }
+            continue;
+        }
+
+        if let Some(c) = p.collected.captures(line) {
+            if let Some(id) = id_for(&c[1]) {
+                wins.push((
+                    pot_number(&c[3], c.get(4)),
+                    PlayerWin {
+                        player_id: id,
+                        win_amount: parse_amount(&c[2])?,
+                        contributed_rake: None,
+                        extra: Extra::new(),
+                    },
+                ));
+            }
+            continue;
+        }
+
+        let (pid, act) = match split_actor(line, &names) {
+            Some(found) => found,
+            None => {
+                debug!("hand {}: skipping line {}", game_number, line);
+                continue;
+            }
+        };
+        let (act, allin) = match act.strip_suffix(" and is all-in") {
+            Some(a) => (a, true),
+            None => (act, false),
+        };
+        let words: Vec<&str> = act.split(' ').collect();
+
+        match words.as_slice() {
+            // any blind after the first of its kind is a dead post from a
+            // returning player, matching how the converter prints them
+            ["posts", "small", "blind", x] | ["posts", "big", "blind", x] => {
+                let amt = parse_amount(x)?;
+                let seen = if words[1] == "small" {
+                    &mut posted_sb
+                } else {
+                    &mut posted_bb
+                };
+                if *seen {
+                    *street.committed.entry(pid.to_string()).or_default() +=
+                        live_dead_amount(sb, bb, amt);
+                    street.push(pid, "Post Dead", Some(amt), allin);
+                } else {
+                    *seen = true;
+                    *street.committed.entry(pid.to_string()).or_default() += amt;
+                    let action = if words[1] == "small" { "Post SB" } else { "Post BB" };
+                    street.push(pid, action, Some(amt), allin);
+                }
+            }
+            ["posts", "small", "&", "big", "blinds", x] => {
+                let amt = parse_amount(x)?;
+                *street.committed.entry(pid.to_string()).or_default() +=
+                    live_dead_amount(sb, bb, amt);
+                street.push(pid, "Post Dead", Some(amt), allin);
+            }
+            ["posts", "the", "ante", x] => {
+                street.push(pid, "Post Ante", Some(parse_amount(x)?), allin);
+            }
+            ["posts", "straddle", x] => {
+                let amt = parse_amount(x)?;
+                *street.committed.entry(pid.to_string()).or_default() += amt;
+                street.push(pid, "Straddle", Some(amt), allin);
+            }
+            ["folds", ..] => street.push(pid, "Fold", None, false),
+            ["checks"] => street.push(pid, "Check", None, false),
+            ["calls", x] | ["bets", x] => {
+                let amt = parse_amount(x)?;
+                *street.committed.entry(pid.to_string()).or_default() += amt;
+                let action = if words[0] == "calls" { "Call" } else { "Bet" };
+                street.push(pid, action, Some(amt), allin);
+            }
+            ["raises", _, "to", to] => {
+                // ohh records what the raise adds, not the new total
+                let to = parse_amount(to)?;
+                let committed = street.committed.entry(pid.to_string()).or_default();
+                let amt = to - *committed;
+                *committed = to;
+                street.push(pid, "Raise", Some(amt), allin);
+            }
+            ["shows", ..] => {
+                let shown = act
+                    .split_once('[')
+                    .and_then(|(_, c)| c.split_once(']'))
+                    .map(|(c, _)| split_cards(c));
+                street.push(pid, "Shows Cards", None, false);
+                if let Some(a) = street.round.actions.last_mut() {
+                    a.cards = shown;
+                }
+            }
+            ["mucks", "hand"] | ["doesn't", "show", "hand"] => {
+                street.push(pid, "Muck", None, false)
+            }
+            _ => debug!("hand {}: skipping action {}", game_number, line),
+        }
+    }
+    rounds.push(street.round);
+
+    // only streets that were dealt or played are kept
+    rounds.retain(|r| r.street == "Preflop" || !r.actions.is_empty() || !r.cards.is_empty());
+    for (i, r) in rounds.iter_mut().enumerate() {
+        r.id = i as u8;
+    }
+
+    // dealt cards follow the blinds, mucked cards only show up in the summary
+    if let Some(preflop) = rounds.first_mut() {
+        let at = preflop
+            .actions
+            .iter()
+            .take_while(|a| crate::converter::is_post(&a.action))
+            .count();
+        let dealt_actions = dealt.iter().map(|(id, c)| Action {
+            action_number: 0,
+            player_id: Some(id.clone()),
+            action: "Dealt Cards".to_string(),
+            amount: None,
+            is_allin: Some(false),
+            cards: Some(c.clone()),
+            extra: Extra::new(),
+        });
+        preflop.actions.splice(at..at, dealt_actions);
+        for (i, a) in preflop.actions.iter_mut().enumerate() {
+            a.action_number = i as u32;
+        }
+    }
+    for (name, cards) in summary_mucks {
+        let id = id_for(&name);
+        let muck = rounds
+            .iter_mut()
+            .flat_map(|r| r.actions.iter_mut())
+            .find(|a| a.action == "Muck" && a.player_id == id);
+        if let Some(a) = muck {
+            a.cards = Some(cards);
+        }
+    }
+
+    let total_pot = total_pot.ok_or("missing total pot line in summary")?;
+    let pot_count = wins
+        .iter()
+        .map(|(i, _)| i + 1)
+        .chain(side_pots.iter().map(|(i, _)| i + 1))
+        .max()
+        .unwrap_or(1);
+    let mut pots: Vec<Pot> = (0..pot_count)
+        .map(|i| Pot {
+            number: i as u8,
+            amount: side_pots
+                .iter()
+                .find(|(idx, _)| *idx == i)
+                .map(|(_, a)| *a)
+                .unwrap_or_default(),
+            rake: Money::ZERO,
+            jackpot: None,
+            player_wins: Vec::new(),
+            extra: Extra::new(),
+        })
+        .collect();
+    // per-pot amounts are printed after rake, which is only known in total,
+    // so all of it is charged to the main pot
+    if pot_count == 1 {
+        pots[0].amount = total_pot;
+    } else {
+        pots[0].amount += rake;
+    }
+    pots[0].rake = rake;
+    wins.reverse();
+    for (idx, win) in wins {
+        pots[idx].player_wins.push(win);
+    }
+
+    let tournament = tournament_info.is_some();
+    let ante_amount = ante.or_else(|| {
+        rounds
+            .iter()
+            .flat_map(|r| &r.actions)
+            .filter(|a| tournament && a.action == "Post Ante")
+            .filter_map(|a| a.amount)
+            .max()
+    });
+    let hero_player_id = match dealt.as_slice() {
+        [(id, _)] => Some(id.clone()),
+        _ => None,
+    };
+
+    let hand = OhhHand {
+        spec_version: None,
+        internal_version: None,
+        game_number,
+        game_type: Some(game_type(&game)),
+        bet_limit: Some(BetLimit {
+            bet_type: Some(bet_type(&limit)),
+            extra: Extra::new(),
+        }),
+        small_blind_amount: sb,
+        big_blind_amount: bb,
+        ante_amount,
+        currency,
+        start_date_utc,
+        table_name,
+        table_size,
+        table_handle: None,
+        table_skin: None,
+        dealer_seat,
+        hero_player_id,
+        site_name: Some("PokerStars".to_string()),
+        network_name: None,
+        tournament,
+        tournament_info,
+        flags: Vec::new(),
+        players,
+        rounds,
+        pots,
+        extra: Extra::new(),
+    };
+    Ok((hand, diagnostics))
+}
+
+// byte offset and text of every hand in a pokerstars export
+pub fn split_hands(text: &str) -> Vec<(usize, &str)> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches('\u{feff}');
+        if trimmed.starts_with("PokerStars ") && trimmed.contains("Hand #") {
+            starts.push(offset + line.len() - trimmed.len());
+        }
+        offset += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(text.len());
+            (start, text[start..end].trim_end())
+        })
+        .collect()
+}
+
+// pokerstars text rather than ohh json
+pub fn is_pokerstars_text(content: &str) -> bool {
+    content
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with("PokerStars")
+}
+
+pub fn pokerstars_to_ohh(content: &str) -> Result<ConvertReport, ConvertError> {
+    pokerstars_to_ohh_with(content, &ConvertOptions::default())
+}
+
+// only the hero, filter and anonymize options apply to ohh output
+pub fn pokerstars_to_ohh_with(
+    content: &str,
+    options: &ConvertOptions,
+) -> Result<ConvertReport, ConvertError> {
+    debug!("pokerstars_to_ohh called with {} bytes", content.len());
+
+    let (mut hands, mut diagnostics) = parse_pokerstars_file(content)?;
+    diagnostics.extend(prepare_hands(&mut hands, options)?);
+    let anonymizer = options
+        .anonymize
+        .as_ref()
+        .map(|anonymize| Anonymizer::new(anonymize, &hands));
+    normalize_prepared_hands(hands, diagnostics, options, anonymizer.as_ref())
+}
+
+// every hand in a pokerstars text file, skipping the ones that do not parse
+pub fn parse_pokerstars_file(content: &str) -> Result<(Vec<OhhHand>, Vec<Diagnostic>), ConvertError> {
+    let mut hands = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for (index, (offset, text)) in split_hands(content).into_iter().enumerate() {
+        match parse_pokerstars_hand(text) {
+            Ok((hand, notes)) => {
+                hands.push(hand);
+                diagnostics.extend(notes);
+            }
+            Err(message) => {
+                let line = content[..offset].matches('\n').count() + 1;
+                warn!("hand {} at line {}: failed to parse: {}", index, line, message);
+                diagnostics.push(Diagnostic::ParseError {
+                    index,
+                    offset,
+                    line,
+                    column: 1,
+                    message,
+                });
+            }
+        }
+    }
+
+    if hands.is_empty() {
+        return Err(ConvertError::NoHands(diagnostics));
+    }
+
+    info!("parsed {} pokerstars hands", hands.len());
+    Ok((hands, diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{ohh_to_pokerstars_text, OhhFile};
+
+    const SIDE_POTS: &str = r#"{"ohh":{"game_number":"sp1","start_date_utc":"2023-12-05T06:23:28.158Z","table_name":"Side Table","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","bet_limit":{"bet_type":"NL"},"players":[{"id":1,"seat":1,"name":"Short","starting_stack":0.5},{"id":2,"seat":2,"name":"Middle: Man","starting_stack":1.0},{"id":3,"seat":3,"name":"Deep","starting_stack":5.0}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":2,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":3,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Raise","amount":0.5,"is_allin":true},{"action_number":3,"player_id":2,"action":"Raise","amount":0.95,"is_allin":true},{"action_number":4,"player_id":3,"action":"Call","amount":0.9}]},{"id":1,"street":"Flop","cards":["Ah","Kd","Qc"],"actions":[]},{"id":2,"street":"Turn","cards":["2s"],"actions":[]},{"id":3,"street":"River","cards":["7h"],"actions":[]},{"id":4,"street":"Showdown","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Shows Cards","cards":["As","Ac"]},{"action_number":1,"player_id":2,"action":"Muck","cards":["9d","8d"]},{"action_number":2,"player_id":3,"action":"Shows Cards","cards":["Kh","Ks"]}]}],"pots":[{"number":0,"amount":1.5,"rake":0.05,"player_wins":[{"player_id":1,"win_amount":1.45}]},{"number":1,"amount":1.0,"rake":0,"player_wins":[{"player_id":3,"win_amount":1.0}]}]}}"#;
+
+    const TOURNAMENT: &str = r#"{"ohh":{"game_number":"t100","start_date_utc":"2023-12-05T02:50:49Z","table_name":"987654 3","table_size":9,"dealer_seat":1,"small_blind_amount":50,"big_blind_amount":100,"ante_amount":10,"tournament":true,"hero_player_id":1,"tournament_info":{"tournament_number":987654,"buyin_amount":10,"fee_amount":1,"bounty_fee_amount":5,"currency":"USD","level":4},"players":[{"id":1,"seat":1,"name":"Player1","starting_stack":1500,"player_bounty":5},{"id":2,"seat":2,"name":"Player2","starting_stack":3250,"player_bounty":7.5}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post Ante","amount":10},{"action_number":1,"player_id":2,"action":"Post Ante","amount":10},{"action_number":2,"player_id":1,"action":"Post SB","amount":50},{"action_number":3,"player_id":2,"action":"Post BB","amount":100},{"action_number":4,"player_id":1,"action":"Dealt Cards","cards":["Js","Jd"]},{"action_number":5,"player_id":1,"action":"Raise","amount":250},{"action_number":6,"player_id":2,"action":"Call","amount":200}]},{"id":1,"street":"Flop","cards":["2c","7d","9h"],"actions":[{"action_number":0,"player_id":2,"action":"Check"},{"action_number":1,"player_id":1,"action":"Bet","amount":300},{"action_number":2,"player_id":2,"action":"Fold"}]}],"pots":[{"number":0,"amount":620,"rake":0,"player_wins":[{"player_id":1,"win_amount":620}]}]}}"#;
+
+    fn hand(json: &str) -> OhhHand {
+        serde_json::from_str::<OhhFile>(json).unwrap().ohh
+    }
+
+    fn round_trip(original: &OhhHand) -> OhhHand {
+        let text = ohh_to_pokerstars_text(original);
+        let (parsed, diagnostics) = parse_pokerstars_hand(&text).unwrap();
+        assert!(diagnostics.is_empty());
+        assert_eq!(ohh_to_pokerstars_text(&parsed), text);
+        parsed
+    }
+
+    #[test]
+    fn test_cash_side_pots_and_showdown_round_trip() {
+        let parsed = round_trip(&hand(SIDE_POTS));
+
+        assert_eq!(parsed.currency.as_deref(), Some("USD"));
+        assert_eq!(parsed.players[1].name, "Middle: Man");
+        assert_eq!(parsed.pots.len(), 2);
+        assert_eq!(parsed.pots[0].amount, Money::from(1.5));
+        assert_eq!(parsed.pots[0].rake, Money::from(0.05));
+        assert_eq!(parsed.pots[1].player_wins[0].player_id, "3");
+
+        let preflop = &parsed.rounds[0].actions;
+        assert_eq!(preflop[3].action, "Raise");
+        assert_eq!(preflop[3].amount, Some(Money::from(0.95)));
+        assert_eq!(preflop[3].is_allin, Some(true));
+
+        let showdown = parsed.rounds.last().unwrap();
+        assert_eq!(showdown.street, "Showdown");
+        assert_eq!(showdown.actions[1].cards, Some(vec!["9d".to_string(), "8d".to_string()]));
+    }
+
+    #[test]
+    fn test_dead_blinds_round_trip() {
+        let dead = r#"{"ohh":{"game_number":"d1","start_date_utc":"2023-12-05T02:50:49Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","bet_limit":{"bet_type":"NL"},"players":[{"id":1,"seat":1,"name":"Dee","starting_stack":10},{"id":2,"seat":2,"name":"Small","starting_stack":10},{"id":3,"seat":3,"name":"Big","starting_stack":10},{"id":4,"seat":4,"name":"Eve","starting_stack":10}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":2,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":3,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Post Dead","amount":0.15},{"action_number":3,"player_id":4,"action":"Post Dead","amount":0.1},{"action_number":4,"player_id":1,"action":"Raise","amount":0.2},{"action_number":5,"player_id":2,"action":"Fold"},{"action_number":6,"player_id":3,"action":"Call","amount":0.2},{"action_number":7,"player_id":4,"action":"Call","amount":0.2}]},{"id":1,"street":"Flop","cards":["2c","7d","9h"],"actions":[{"action_number":0,"player_id":3,"action":"Check"},{"action_number":1,"player_id":4,"action":"Check"},{"action_number":2,"player_id":1,"action":"Bet","amount":0.5},{"action_number":3,"player_id":3,"action":"Fold"},{"action_number":4,"player_id":4,"action":"Fold"}]}],"pots":[{"number":0,"amount":1.0,"rake":0,"player_wins":[{"player_id":1,"win_amount":1.0}]}]}}"#;
+        let text = ohh_to_pokerstars_text(&hand(dead));
+        assert!(text.contains("Dee: posts small & big blinds $0.15\nEve: posts big blind $0.10\n"));
+        assert!(text.contains("Dee: raises $0.20 to $0.30\n"));
+        assert!(text.contains("Eve: calls $0.20\n"));
+
+        let parsed = round_trip(&hand(dead));
+        let preflop = &parsed.rounds[0].actions;
+        assert_eq!(preflop[4].action, "Raise");
+        assert_eq!(preflop[4].amount, Some(Money::from(0.2)));
+    }
+
+    #[test]
+    fn test_tournament_with_antes_round_trip() {
+        let parsed = round_trip(&hand(TOURNAMENT));
+
+        let info = parsed.tournament_info.as_ref().unwrap();
+        assert_eq!(info.tournament_number.as_deref(), Some("987654"));
+        assert_eq!(info.bounty_fee_amount, Some(Money::from(5.0)));
+        assert_eq!(info.level, Some(4));
+        assert_eq!(parsed.ante_amount, Some(Money::from(10.0)));
+        assert_eq!(parsed.players[1].player_bounty, Some(Money::from(7.5)));
+        assert_eq!(parsed.hero_player_id.as_deref(), Some("1"));
+        assert_eq!(parsed.rounds[0].actions[0].action, "Post Ante");
+        assert_eq!(parsed.rounds[0].actions[5].amount, Some(Money::from(250.0)));
+    }
+
+    #[test]
+    fn test_parses_pokerstars_client_output() {
+        let text = "PokerStars Hand #2500000001:  Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 18:05:09 CET [2020/01/01 12:05:09 ET]
+Table 'Alcyone II' 6-max Seat #2 is the button
+Seat 1: alice ($2.13 in chips)
+Seat 2: bob ($1,250.50 in chips) is sitting out
+Seat 3: carol ($2 in chips)
+carol: posts small blind $0.01
+alice: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to carol [Ah Kd]
+carol: raises $0.04 to $0.06
+alice: calls $0.04
+*** FLOP *** [2c 7d 9h]
+alice: checks
+carol: bets $0.10
+alice: folds
+Uncalled bet ($0.10) returned to carol
+carol collected $0.12 from pot
+carol: doesn't show hand
+*** SUMMARY ***
+Total pot $0.12 | Rake $0
+Board [2c 7d 9h]
+Seat 1: alice (big blind) folded on the Flop
+Seat 3: carol (small blind) collected ($0.12)
+";
+        let (parsed, diagnostics) = parse_pokerstars_hand(text).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(parsed.game_number, "2500000001");
+        assert_eq!(parsed.start_date_utc, "2020-01-01T17:05:09Z");
+        assert_eq!(parsed.players[1].starting_stack, Money::from(1250.5));
+        assert_eq!(parsed.rounds[0].actions[3].amount, Some(Money::from(0.05)));
+        assert_eq!(parsed.rounds[1].cards, vec!["2c", "7d", "9h"]);
+        assert_eq!(parsed.pots[0].player_wins[0].win_amount, Money::from(0.12));
+    }
+
+    #[test]
+    fn test_report_skips_unreadable_hands() {
+        let good = ohh_to_pokerstars_text(&hand(TOURNAMENT));
+        let bad = "PokerStars Hand #x1: Razz (1/2) - 2020/01/01 18:05:09 ET\nTable 'T' 6-max Seat #1 is the button\n";
+        let content = format!("{}\n\n\n{}\n\n{}\n", good, bad, good.replace("t100", "t101"));
+
+        let report = pokerstars_to_ohh(&content).unwrap();
+        assert_eq!(report.hands, vec!["t100", "t101"]);
+        assert_eq!(report.skipped(), 1);
+        assert!(matches!(
+            &report.diagnostics[0],
+            Diagnostic::ParseError { index: 1, line, .. } if *line == good.lines().count() + 3
+        ));
+
+        let reparsed = crate::converter::convert_ohh_file(&report.output).unwrap();
+        assert_eq!(reparsed.hands, vec!["t100", "t101"]);
+    }
+
+    #[test]
+    fn test_header_times_in_utc() {
+        let header = |time: &str| {
+            let text = ohh_to_pokerstars_text(&hand(TOURNAMENT))
+                .replace("2023-12-05 02:50:49 UTC", time);
+            parse_pokerstars_hand(&text).unwrap()
+        };
+        let utc = |time: &str| header(time).0.start_date_utc;
+
+        assert_eq!(
+            utc("2020/07/01 18:05:09 CEST [2020/07/01 12:05:09 ET]"),
+            "2020-07-01T16:05:09Z"
+        );
+        assert_eq!(utc("2020/01/01 12:05:09 ET"), "2020-01-01T17:05:09Z");
+        assert_eq!(utc("2020/07/01 12:05:09 ET"), "2020-07-01T16:05:09Z");
+        assert_eq!(utc("2020/03/08 01:59:59 ET"), "2020-03-08T06:59:59Z");
+        assert_eq!(utc("2020/03/08 03:00:00 ET"), "2020-03-08T07:00:00Z");
+        assert_eq!(utc("2020/11/01 01:30:00 ET"), "2020-11-01T05:30:00Z");
+        assert_eq!(utc("2020/01/01 12:05:09 EST"), "2020-01-01T17:05:09Z");
+        assert_eq!(utc("2020/01/01 07:05:09 EDT"), "2020-01-01T11:05:09Z");
+        // a zone we do not know falls back on the eastern time in brackets
+        assert_eq!(
+            utc("2020/01/01 20:05:09 MSK [2020/01/01 12:05:09 ET]"),
+            "2020-01-01T17:05:09Z"
+        );
+
+        let (parsed, diagnostics) = header("2020/01/01 20:05:09 MSK");
+        assert_eq!(parsed.start_date_utc, "2020-01-01T20:05:09Z");
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::UnknownTimeZone {
+                game_number: "t100".to_string(),
+                zone: "MSK".to_string(),
+            }]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "hand t100: unknown time zone MSK, start time kept as printed"
+        );
+        assert!(matches!(
+            &header("2020/01/01 20:05:09").1[..],
+            [Diagnostic::UnknownTimeZone { zone, .. }] if zone.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_from_roman() {
+        assert_eq!(from_roman("IV"), Some(4));
+        assert_eq!(from_roman("XIV"), Some(14));
+        assert_eq!(from_roman("MCMXCIV"), Some(1994));
+        assert_eq!(from_roman(""), None);
+        assert_eq!(from_roman("IIX2"), None);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_full_sample_round_trip() {
+        let content = std::fs::read_to_string("../hands-pglCX2WsUJbPBjsNSE1siiDJy.ohh.txt")
+            .expect("Failed to read sample file. Make sure to run from src-tauri directory.");
+        let (hands, _) = crate::converter::parse_ohh_chunks(&content).unwrap();
+
+        // boards that were run twice do not fit the single board pokerstars prints
+        for h in hands.iter().filter(|h| crate::converter::board_cards(h).len() <= 5) {
+            round_trip(h);
+        }
+    }
+}
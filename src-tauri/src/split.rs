@@ -0,0 +1,395 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Cursor, Write};
+
+use crate::converter::OhhHand;
+
+// a new session starts after this long without a hand at the table
+pub const SESSION_GAP_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitBy {
+    Table,
+    // hands at one table without a long break between them
+    Session,
+    // the utc date the hand started
+    Day,
+}
+
+impl SplitBy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "table" => Some(SplitBy::Table),
+            "session" => Some(SplitBy::Session),
+            "day" | "date" => Some(SplitBy::Day),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitOptions {
+    #[serde(default)]
+    pub by: Option<SplitBy>,
+    #[serde(default)]
+    pub max_hands: Option<usize>,
+    // a hand bigger than this still goes out, in a part of its own
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+}
+
+impl SplitOptions {
+    fn limited(&self) -> bool {
+        self.max_hands.is_some() || self.max_bytes.is_some()
+    }
+}
+
+// where one hand's text sits in a report's output
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandSpan {
+    pub game_number: String,
+    pub table_name: String,
+    pub start_date_utc: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl HandSpan {
+    pub fn new(h: &OhhHand, start: usize, end: usize) -> Self {
+        HandSpan {
+            game_number: h.game_number.clone(),
+            table_name: h.table_name.clone(),
+            start_date_utc: h.start_date_utc.clone(),
+            start,
+            end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputPart {
+    // what goes between the file stem and the extension, e.g. "Zurich_002"
+    pub key: String,
+    pub hands: Vec<String>,
+    pub output: String,
+}
+
+// "hands" + "2023-12-05" + "pokerstars.txt" -> "hands_2023-12-05.pokerstars.txt"
+pub fn part_file_name(stem: &str, key: &str, extension: &str) -> String {
+    format!("{}_{}.{}", stem, key, extension)
+}
+
+// table names can hold anything, file names should not
+fn slug(text: &str) -> String {
+    let slug: String = text
+        .trim()
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '.' => c,
+            _ => '_',
+        })
+        .collect();
+    let slug = slug.trim_matches(['_', '.']).to_string();
+    if slug.is_empty() {
+        "table".to_string()
+    } else {
+        slug
+    }
+}
+
+fn start_time(span: &HandSpan) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&span.start_date_utc)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+// "2023-12-05T02:47:13.126Z" -> "2023-12-05_0247"
+fn minute_key(date: &str) -> String {
+    let day = date.get(..10).unwrap_or(date);
+    let time = date.get(11..16).unwrap_or("").replace(':', "");
+    if time.is_empty() {
+        day.to_string()
+    } else {
+        format!("{}_{}", day, time)
+    }
+}
+
+// hands by the key of the file they go to, each group in output order
+fn group(spans: &[HandSpan], by: Option<SplitBy>) -> BTreeMap<String, Vec<&HandSpan>> {
+    let mut groups: BTreeMap<String, Vec<&HandSpan>> = BTreeMap::new();
+    match by {
+        None => {
+            groups.insert(String::new(), spans.iter().collect());
+        }
+        Some(SplitBy::Table) => {
+            for span in spans {
+                groups
+                    .entry(span.table_name.clone())
+                    .or_default()
+                    .push(span);
+            }
+        }
+        Some(SplitBy::Day) => {
+            for span in spans {
+                let day = span.start_date_utc.get(..10).unwrap_or("undated");
+                groups.entry(day.to_string()).or_default().push(span);
+            }
+        }
+        Some(SplitBy::Session) => {
+            let mut tables: BTreeMap<&str, Vec<&HandSpan>> = BTreeMap::new();
+            for span in spans {
+                tables.entry(&span.table_name).or_default().push(span);
+            }
+            for (table, mut hands) in tables {
+                hands.sort_by(|a, b| a.start_date_utc.cmp(&b.start_date_utc));
+                let mut key = String::new();
+                let mut last: Option<DateTime<Utc>> = None;
+                for span in hands {
+                    let time = start_time(span);
+                    let gap = match (last, time) {
+                        (Some(last), Some(time)) => (time - last).num_minutes(),
+                        _ => 0,
+                    };
+                    if key.is_empty() || gap > SESSION_GAP_MINUTES {
+                        key = format!("{}\u{0}{}", table, minute_key(&span.start_date_utc));
+                    }
+                    last = time.or(last);
+                    groups.entry(key.clone()).or_default().push(span);
+                }
+            }
+        }
+    }
+    groups
+}
+
+// cuts `output` into parts, keeping every hand whole. parts are named by
+// group and numbered within it only when there is a size limit, so the same
+// hands and options always give the same names
+pub fn split_output(
+    output: &str,
+    spans: &[HandSpan],
+    separator: &str,
+    options: &SplitOptions,
+) -> Vec<OutputPart> {
+    let mut parts = Vec::new();
+    let mut taken = HashSet::new();
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+
+    for (group_key, hands) in group(spans, options.by) {
+        let mut name = group_key
+            .split('\u{0}')
+            .map(slug)
+            .collect::<Vec<_>>()
+            .join("_");
+        if options.by.is_none() {
+            name.clear();
+        }
+        // two tables can differ only in characters the slug drops
+        let base = name.clone();
+        let mut n = 2;
+        while !name.is_empty() && !taken.insert(name.clone()) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let mut chunk: Vec<&HandSpan> = Vec::new();
+        let mut bytes = 0;
+        let mut chunks = Vec::new();
+        for span in hands {
+            let len = span.end - span.start;
+            let extra = if chunk.is_empty() {
+                len
+            } else {
+                len + separator.len()
+            };
+            let full = options.max_hands.is_some_and(|max| chunk.len() >= max)
+                || options.max_bytes.is_some_and(|max| bytes + extra > max);
+            if full && !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+                bytes = 0;
+            }
+            bytes += if chunk.is_empty() {
+                len
+            } else {
+                len + separator.len()
+            };
+            chunk.push(span);
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        for chunk in chunks {
+            let key = if options.limited() || name.is_empty() {
+                let number = numbers.entry(name.clone()).or_default();
+                *number += 1;
+                if name.is_empty() {
+                    format!("{:03}", number)
+                } else {
+                    format!("{}_{:03}", name, number)
+                }
+            } else {
+                name.clone()
+            };
+            let texts: Vec<&str> = chunk.iter().map(|s| &output[s.start..s.end]).collect();
+            parts.push(OutputPart {
+                key,
+                hands: chunk.iter().map(|s| s.game_number.clone()).collect(),
+                output: texts.join(separator),
+            });
+        }
+    }
+    parts
+}
+
+// one file of a zip download
+#[derive(Debug, Clone, Deserialize)]
+pub struct ZipEntry {
+    pub name: String,
+    pub output: String,
+}
+
+// a deflated zip of (file name, text) pairs, with fixed timestamps so the
+// same parts give the same bytes
+pub fn zip_files<'a, I>(files: I) -> Result<Vec<u8>, String>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default());
+    for (name, text) in files {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(text.as_bytes()).map_err(Into::into))
+            .map_err(|e| format!("cannot add {} to the zip: {}", name, e))?;
+    }
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("cannot write the zip: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // hands as "game@table@date" texts joined by "|"
+    fn report(hands: &[(&str, &str, &str)]) -> (String, Vec<HandSpan>) {
+        let mut output = String::new();
+        let mut spans = Vec::new();
+        for (game, table, date) in hands {
+            if !output.is_empty() {
+                output.push('|');
+            }
+            let start = output.len();
+            output.push_str(game);
+            spans.push(HandSpan {
+                game_number: game.to_string(),
+                table_name: table.to_string(),
+                start_date_utc: date.to_string(),
+                start,
+                end: output.len(),
+            });
+        }
+        (output, spans)
+    }
+
+    fn keys(parts: &[OutputPart]) -> Vec<(&str, &str)> {
+        parts
+            .iter()
+            .map(|p| (p.key.as_str(), p.output.as_str()))
+            .collect()
+    }
+
+    const HANDS: [(&str, &str, &str); 5] = [
+        ("g1", "Zurich 6-max", "2023-12-05T02:47:13.126Z"),
+        ("g2", "Oslo", "2023-12-05T02:48:00Z"),
+        ("g3", "Zurich 6-max", "2023-12-05T03:05:00Z"),
+        ("g4", "Zurich 6-max", "2023-12-05T23:50:00Z"),
+        ("g55", "Oslo", "2023-12-06T00:10:00Z"),
+    ];
+
+    #[test]
+    fn test_split_by_size() {
+        let (output, spans) = report(&HANDS);
+        let by_count = SplitOptions {
+            max_hands: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            keys(&split_output(&output, &spans, "|", &by_count)),
+            [("001", "g1|g2"), ("002", "g3|g4"), ("003", "g55")]
+        );
+
+        // a part may hold "g1|g2" but not "g1|g2|g3"; g55 fits nowhere else
+        let by_size = SplitOptions {
+            max_bytes: Some(6),
+            ..Default::default()
+        };
+        assert_eq!(
+            keys(&split_output(&output, &spans, "|", &by_size)),
+            [("001", "g1|g2"), ("002", "g3|g4"), ("003", "g55")]
+        );
+        let tiny = SplitOptions {
+            max_bytes: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(split_output(&output, &spans, "|", &tiny).len(), 5);
+    }
+
+    #[test]
+    fn test_split_by_table_session_and_day() {
+        let (output, spans) = report(&HANDS);
+        let split = |by, max_hands| {
+            let options = SplitOptions {
+                by: Some(by),
+                max_hands,
+                max_bytes: None,
+            };
+            split_output(&output, &spans, "|", &options)
+        };
+
+        assert_eq!(
+            keys(&split(SplitBy::Table, None)),
+            [("Oslo", "g2|g55"), ("Zurich_6-max", "g1|g3|g4")]
+        );
+        assert_eq!(
+            keys(&split(SplitBy::Table, Some(2))),
+            [
+                ("Oslo_001", "g2|g55"),
+                ("Zurich_6-max_001", "g1|g3"),
+                ("Zurich_6-max_002", "g4")
+            ]
+        );
+        assert_eq!(
+            keys(&split(SplitBy::Session, None)),
+            [
+                ("Oslo_2023-12-05_0248", "g2"),
+                ("Oslo_2023-12-06_0010", "g55"),
+                ("Zurich_6-max_2023-12-05_0247", "g1|g3"),
+                ("Zurich_6-max_2023-12-05_2350", "g4")
+            ]
+        );
+        assert_eq!(
+            keys(&split(SplitBy::Day, None)),
+            [("2023-12-05", "g1|g2|g3|g4"), ("2023-12-06", "g55")]
+        );
+        assert_eq!(
+            part_file_name("hands", "2023-12-06", "pokerstars.txt"),
+            "hands_2023-12-06.pokerstars.txt"
+        );
+    }
+
+    #[test]
+    fn test_zip_is_reproducible() {
+        let files = [("a_001.txt", "first"), ("a_002.txt", "second")];
+        let zip = zip_files(files).unwrap();
+        assert!(zip.starts_with(b"PK\x03\x04"));
+        // the local header's modified time and date: 1980-01-01 00:00:00
+        assert_eq!(zip[10..14], [0x00, 0x00, 0x21, 0x00]);
+        assert_eq!(zip, zip_files(files).unwrap());
+    }
+}
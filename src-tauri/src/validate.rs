@@ -0,0 +1,348 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::converter::{is_post, live_post_amount, street_actions, uncalled_bet, OhhHand};
+use crate::money::Money;
+
+// a reason the hand could not have been played as recorded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    DuplicateSeat {
+        seat: u8,
+    },
+    DuplicatePlayerId {
+        player_id: String,
+    },
+    MissingPlayerId {
+        street: String,
+        action_number: u32,
+    },
+    UnknownPlayer {
+        street: String,
+        action_number: u32,
+        player_id: String,
+    },
+    NegativeAmount {
+        street: String,
+        action_number: u32,
+        amount: Money,
+    },
+    ActsAfterFolding {
+        street: String,
+        action_number: u32,
+        player_id: String,
+    },
+    OverStack {
+        player_id: String,
+        committed: Money,
+        stack: Money,
+    },
+    PotMismatch {
+        contributed: Money,
+        pots: Money,
+    },
+    PotPayout {
+        pot: u8,
+        amount: Money,
+        paid: Money,
+    },
+    UnknownWinner {
+        pot: u8,
+        player_id: String,
+    },
+    Overflow,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::DuplicateSeat { seat } => write!(f, "seat {} is taken by more than one player", seat),
+            Issue::DuplicatePlayerId { player_id } => {
+                write!(f, "player id {} is used by more than one player", player_id)
+            }
+            Issue::MissingPlayerId {
+                street,
+                action_number,
+            } => write!(f, "{} action {} has no player id", street, action_number),
+            Issue::UnknownPlayer {
+                street,
+                action_number,
+                player_id,
+            } => write!(
+                f,
+                "{} action {} refers to unknown player id {}",
+                street, action_number, player_id
+            ),
+            Issue::NegativeAmount {
+                street,
+                action_number,
+                amount,
+            } => write!(
+                f,
+                "{} action {} has a negative amount {}",
+                street, action_number, amount
+            ),
+            Issue::ActsAfterFolding {
+                street,
+                action_number,
+                player_id,
+            } => write!(
+                f,
+                "{} action {}: player {} acts after folding",
+                street, action_number, player_id
+            ),
+            Issue::OverStack {
+                player_id,
+                committed,
+                stack,
+            } => write!(
+                f,
+                "player {} puts in {} with a starting stack of {}",
+                player_id, committed, stack
+            ),
+            Issue::PotMismatch { contributed, pots } => write!(
+                f,
+                "bets add up to {} but the pots total {}",
+                contributed, pots
+            ),
+            Issue::PotPayout { pot, amount, paid } => write!(
+                f,
+                "pot {} holds {} but wins, rake and jackpot add up to {}",
+                pot, amount, paid
+            ),
+            Issue::UnknownWinner { pot, player_id } => {
+                write!(f, "pot {} is won by unknown player id {}", pot, player_id)
+            }
+            Issue::Overflow => write!(f, "amounts too large to reconcile the pot"),
+        }
+    }
+}
+
+fn add(total: &mut Money, amount: Money, overflowed: &mut bool) {
+    match total.checked_add(amount) {
+        Some(sum) => *total = sum,
+        None => *overflowed = true,
+    }
+}
+
+// replays the action stream the same way the converter does and reports
+// anything that cannot add up: unknown players, betting past a stack, bets
+// that do not match the pots and pots that do not match their payouts
+pub fn validate(h: &OhhHand) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut seats = HashSet::new();
+    let mut stacks: HashMap<&str, Money> = HashMap::new();
+    for p in &h.players {
+        if !seats.insert(p.seat) {
+            issues.push(Issue::DuplicateSeat { seat: p.seat });
+        }
+        if stacks.insert(p.id.as_str(), p.starting_stack).is_some() {
+            issues.push(Issue::DuplicatePlayerId {
+                player_id: p.id.clone(),
+            });
+        }
+    }
+
+    let mut put_in: HashMap<String, Money> = HashMap::new();
+    let mut folded: HashSet<String> = HashSet::new();
+    let mut contributed = Money::ZERO;
+    let mut refunded = Money::ZERO;
+    let mut overflowed = false;
+
+    for round in &h.rounds {
+        let street = &round.street;
+        let mut committed: HashMap<String, Money> = HashMap::new();
+
+        let actions = street_actions(h, round);
+
+        for a in &actions {
+            if let Some(amt) = a.amount {
+                if amt < Money::ZERO {
+                    issues.push(Issue::NegativeAmount {
+                        street: street.clone(),
+                        action_number: a.action_number,
+                        amount: amt,
+                    });
+                }
+                add(&mut contributed, amt, &mut overflowed);
+            }
+
+            let pid = match &a.player_id {
+                Some(pid) => pid,
+                None => {
+                    issues.push(Issue::MissingPlayerId {
+                        street: street.clone(),
+                        action_number: a.action_number,
+                    });
+                    continue;
+                }
+            };
+            if !stacks.contains_key(pid.as_str()) {
+                issues.push(Issue::UnknownPlayer {
+                    street: street.clone(),
+                    action_number: a.action_number,
+                    player_id: pid.clone(),
+                });
+                continue;
+            }
+
+            match a.action.as_str() {
+                "Fold" | "Check" | "Call" | "Bet" | "Raise" if folded.contains(pid) => {
+                    issues.push(Issue::ActsAfterFolding {
+                        street: street.clone(),
+                        action_number: a.action_number,
+                        player_id: pid.clone(),
+                    });
+                }
+                "Fold" => {
+                    folded.insert(pid.clone());
+                }
+                _ => {}
+            }
+
+            if let Some(amt) = a.amount {
+                add(put_in.entry(pid.clone()).or_default(), amt, &mut overflowed);
+
+                let live = if is_post(&a.action) {
+                    live_post_amount(h, &a.action, amt)
+                } else {
+                    amt
+                };
+                if live.is_positive() {
+                    add(committed.entry(pid.clone()).or_default(), live, &mut overflowed);
+                }
+            }
+        }
+
+        if let Some((_, returned)) = uncalled_bet(&committed) {
+            add(&mut refunded, returned, &mut overflowed);
+        }
+    }
+
+    // the uncalled part of a bet still has to come out of the stack
+    for p in &h.players {
+        if let Some(&total) = put_in.get(&p.id) {
+            if total > p.starting_stack {
+                issues.push(Issue::OverStack {
+                    player_id: p.id.clone(),
+                    committed: total,
+                    stack: p.starting_stack,
+                });
+            }
+        }
+    }
+
+    let mut pots = Money::ZERO;
+    for pot in &h.pots {
+        add(&mut pots, pot.amount, &mut overflowed);
+
+        // pot amounts include the rake taken from them
+        let mut paid = pot.rake;
+        add(&mut paid, pot.jackpot.unwrap_or_default(), &mut overflowed);
+        for win in &pot.player_wins {
+            add(&mut paid, win.win_amount, &mut overflowed);
+            if !stacks.contains_key(win.player_id.as_str()) {
+                issues.push(Issue::UnknownWinner {
+                    pot: pot.number,
+                    player_id: win.player_id.clone(),
+                });
+            }
+        }
+        if paid != pot.amount {
+            issues.push(Issue::PotPayout {
+                pot: pot.number,
+                amount: pot.amount,
+                paid,
+            });
+        }
+    }
+
+    if overflowed {
+        issues.push(Issue::Overflow);
+    } else if contributed - refunded != pots {
+        issues.push(Issue::PotMismatch {
+            contributed: contributed - refunded,
+            pots,
+        });
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::OhhFile;
+
+    const HAND: &str = r#"{"ohh":{"game_number":"v1","start_date_utc":"2023-12-05T02:50:49.886Z","table_name":"T","table_size":6,"dealer_seat":1,"small_blind_amount":0.05,"big_blind_amount":0.1,"currency":"USD","players":[{"id":1,"seat":1,"name":"A","starting_stack":1},{"id":2,"seat":2,"name":"B","starting_stack":1}],"rounds":[{"id":0,"street":"Preflop","cards":[],"actions":[{"action_number":0,"player_id":1,"action":"Post SB","amount":0.05},{"action_number":1,"player_id":2,"action":"Post BB","amount":0.1},{"action_number":2,"player_id":1,"action":"Raise","amount":0.25},{"action_number":3,"player_id":2,"action":"Call","amount":0.2}]},{"id":1,"street":"Flop","cards":["Ah","Kd","Qc"],"actions":[{"action_number":0,"player_id":2,"action":"Bet","amount":0.5},{"action_number":1,"player_id":1,"action":"Fold"}]}],"pots":[{"number":0,"amount":0.6,"rake":0.02,"player_wins":[{"player_id":2,"win_amount":0.58}]}]}}"#;
+
+    fn hand(json: &str) -> OhhHand {
+        serde_json::from_str::<OhhFile>(json).unwrap().ohh
+    }
+
+    #[test]
+    fn test_consistent_hand_has_no_issues() {
+        assert_eq!(validate(&hand(HAND)), vec![]);
+    }
+
+    #[test]
+    fn test_flags_impossible_hands() {
+        let broken = HAND
+            .replace(r#""player_id":2,"action":"Bet","amount":0.5"#, r#""player_id":2,"action":"Bet","amount":1.5"#)
+            .replace(r#""player_id":1,"action":"Fold"}]"#, r#""player_id":1,"action":"Fold"},{"action_number":2,"player_id":7,"action":"Check"}]"#)
+            .replace(r#""win_amount":0.58"#, r#""win_amount":0.5"#);
+        let issues = validate(&hand(&broken));
+
+        assert_eq!(
+            issues,
+            vec![
+                Issue::UnknownPlayer {
+                    street: "Flop".to_string(),
+                    action_number: 2,
+                    player_id: "7".to_string(),
+                },
+                Issue::OverStack {
+                    player_id: "2".to_string(),
+                    committed: Money::from(1.8),
+                    stack: Money::from(1.0),
+                },
+                Issue::PotPayout {
+                    pot: 0,
+                    amount: Money::from(0.6),
+                    paid: Money::from(0.52),
+                },
+            ]
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            "player 2 puts in 1.8 with a starting stack of 1"
+        );
+    }
+
+    #[test]
+    fn test_pot_mismatch_and_acting_after_fold() {
+        let broken = HAND
+            .replace(r#""amount":0.6,"rake""#, r#""amount":0.7,"rake""#)
+            .replace(r#""win_amount":0.58"#, r#""win_amount":0.68"#)
+            .replace(r#""player_id":1,"action":"Fold"}]"#, r#""player_id":1,"action":"Fold"},{"action_number":2,"player_id":1,"action":"Call","amount":0.5}]"#);
+        let issues = validate(&hand(&broken));
+
+        assert_eq!(
+            issues,
+            vec![
+                Issue::ActsAfterFolding {
+                    street: "Flop".to_string(),
+                    action_number: 2,
+                    player_id: "1".to_string(),
+                },
+                Issue::PotMismatch {
+                    contributed: Money::from(1.6),
+                    pots: Money::from(0.7),
+                },
+            ]
+        );
+    }
+}
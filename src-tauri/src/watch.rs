@@ -0,0 +1,491 @@
+use log::{debug, info, warn};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::anonymize::PseudonymStyle;
+use crate::converter::{convert_hands, parse_filter, ConvertOptions, AUTO_HERO};
+use crate::diagnostics::{ConvertError, Diagnostic};
+use crate::framing::frame_ohh;
+
+pub const CURSOR_FILE: &str = ".pab-converter-cursor.json";
+
+// quiet time before a changed file is read, and the longest a file that is
+// written to non-stop waits
+const SETTLE: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(3);
+
+// how far each input file has been converted. it is kept in the output
+// folder so a restarted watch carries on without repeating hands
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub files: BTreeMap<String, FileCursor>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileCursor {
+    pub offset: usize,
+    pub hands: usize,
+}
+
+impl Cursor {
+    pub fn load(output_dir: &Path) -> Cursor {
+        let path = output_dir.join(CURSOR_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                warn!("ignoring unreadable cursor {}: {}", path.display(), e);
+                Cursor::default()
+            }),
+            Err(_) => Cursor::default(),
+        }
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<(), String> {
+        let path = output_dir.join(CURSOR_FILE);
+        let tmp = path.with_extension("tmp");
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("cannot save cursor {}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchUpdate {
+    pub file: String,
+    pub output: String,
+    pub hands: Vec<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+// .ohh and .json exports, plus the .ohh.txt files some clients write
+pub fn is_ohh_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name.to_lowercase(),
+        None => return false,
+    };
+    !name.starts_with('.')
+        && (name.ends_with(".ohh") || name.ends_with(".json") || name.ends_with(".ohh.txt"))
+}
+
+// named after the whole file name, since "hands.ohh" and "hands.json" in the
+// watched folder would otherwise append to, and truncate, the same output
+pub fn output_path(path: &Path, output_dir: &Path, options: &ConvertOptions) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "hands".to_string());
+    output_dir.join(format!("{}.{}.txt", name, options.dialect.name()))
+}
+
+fn write_output(path: &Path, text: &str, fresh: bool, separator: &str) -> Result<(), String> {
+    let result = if fresh {
+        fs::write(path, text)
+    } else {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut f| {
+                if f.metadata()?.len() > 0 {
+                    f.write_all(separator.as_bytes())?;
+                }
+                f.write_all(text.as_bytes())
+            })
+    };
+    result.map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// converts whatever was appended to `path` since the cursor and adds the text
+// to its output file. a record that is still being written is left for the
+// next call
+pub fn convert_new_hands(
+    path: &Path,
+    output_dir: &Path,
+    cursor: &mut Cursor,
+    options: &ConvertOptions,
+) -> Result<Option<WatchUpdate>, String> {
+    let key = path.to_string_lossy().into_owned();
+    let bytes = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+
+    let previous = cursor.files.get(&key).cloned().unwrap_or_default();
+    let (start, done) = if previous.offset > bytes.len() {
+        info!(
+            "{} got shorter, converting it again from the start",
+            path.display()
+        );
+        (0, 0)
+    } else {
+        (previous.offset, previous.hands)
+    };
+
+    // a write can also stop in the middle of a character
+    let tail = &bytes[start..];
+    let tail = match std::str::from_utf8(tail) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&tail[..e.valid_up_to()]).unwrap_or_default(),
+    };
+
+    let mut hands = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut consumed = 0;
+    for record in frame_ohh(tail) {
+        match record {
+            Ok(framed) => {
+                consumed = framed.end;
+                hands.push(framed.hand);
+            }
+            Err(e) if e.truncated => break,
+            // a bad record is reported once, then read past
+            Err(e) => {
+                consumed = e.end;
+                diagnostics.push(Diagnostic::from(e));
+            }
+        }
+    }
+
+    if hands.is_empty() && diagnostics.is_empty() {
+        return Ok(None);
+    }
+    debug!(
+        "{}: {} new hands after byte {}",
+        path.display(),
+        hands.len(),
+        start
+    );
+
+    let out = output_path(path, output_dir, options);
+    let mut update = WatchUpdate {
+        file: key.clone(),
+        output: out.to_string_lossy().into_owned(),
+        hands: Vec::new(),
+        warnings: Vec::new(),
+        error: None,
+    };
+
+    let result = if hands.is_empty() {
+        Err(ConvertError::NoHands(diagnostics))
+    } else {
+        convert_hands(hands, diagnostics, options)
+    };
+    match result {
+        Ok(report) => {
+            let separator = options.dialect.writer().separator();
+            write_output(&out, &report.output, start == 0, separator)?;
+            update.hands = report.hands;
+            update.warnings = report.warnings;
+        }
+        // new hands the filter skips are not a problem with the file
+        Err(ConvertError::NothingMatched) => {}
+        Err(e) => update.error = Some(e.to_string()),
+    }
+
+    cursor.files.insert(
+        key,
+        FileCursor {
+            offset: start + consumed,
+            hands: done + update.hands.len(),
+        },
+    );
+    cursor.save(output_dir)?;
+
+    Ok(Some(update))
+}
+
+// stops watching when dropped
+pub struct DirWatcher {
+    watcher: Option<notify::RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DirWatcher {
+    // blocks for as long as the watch runs
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        // dropping the watcher closes the event channel, which ends the thread
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// converts every ohh file in `input_dir` into `output_dir`: what was added
+// since the last run first, then each file again as it changes
+pub fn watch_dir<F>(
+    input_dir: &Path,
+    output_dir: &Path,
+    options: ConvertOptions,
+    mut on_update: F,
+) -> Result<DirWatcher, String>
+where
+    F: FnMut(WatchUpdate) + Send + 'static,
+{
+    let input_dir = input_dir
+        .canonicalize()
+        .map_err(|e| format!("cannot watch {}: {}", input_dir.display(), e))?;
+    let output_dir = fs::create_dir_all(output_dir)
+        .and_then(|_| output_dir.canonicalize())
+        .map_err(|e| format!("cannot use {}: {}", output_dir.display(), e))?;
+    if input_dir == output_dir {
+        return Err("the output folder must be different from the watched folder".to_string());
+    }
+    // sequential pseudonyms would start over with every batch of new hands
+    if options
+        .anonymize
+        .as_ref()
+        .is_some_and(|a| a.style == PseudonymStyle::Sequential)
+    {
+        return Err("anonymizing while watching needs hashed pseudonyms".to_string());
+    }
+    // as would the detected hero, and one output could end up with two
+    if options
+        .hero
+        .as_deref()
+        .is_some_and(|hero| hero.eq_ignore_ascii_case(AUTO_HERO))
+    {
+        return Err(
+            "detecting the hero is not supported while watching, name the hero".to_string(),
+        );
+    }
+    if options.split.is_some() {
+        return Err("splitting the output is not supported while watching".to_string());
+    }
+    parse_filter(&options).map_err(|e| e.to_string())?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    watcher
+        .watch(&input_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("cannot watch {}: {}", input_dir.display(), e))?;
+    info!(
+        "watching {} into {}",
+        input_dir.display(),
+        output_dir.display()
+    );
+
+    let thread = std::thread::spawn(move || {
+        let mut cursor = Cursor::load(&output_dir);
+        let mut pending: BTreeSet<PathBuf> = fs::read_dir(&input_dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        let mut last_run = Instant::now() - MAX_DELAY;
+
+        loop {
+            let settled = match rx.recv_timeout(SETTLE) {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        pending.extend(event.paths);
+                    }
+                    false
+                }
+                Ok(Err(e)) => {
+                    warn!("watch error: {}", e);
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => true,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            if pending.is_empty() || !(settled || last_run.elapsed() >= MAX_DELAY) {
+                continue;
+            }
+            last_run = Instant::now();
+
+            for path in std::mem::take(&mut pending) {
+                if !is_ohh_file(&path) || path.starts_with(&output_dir) || !path.is_file() {
+                    continue;
+                }
+                match convert_new_hands(&path, &output_dir, &mut cursor, &options) {
+                    Ok(Some(update)) => on_update(update),
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("{}", e);
+                        on_update(WatchUpdate {
+                            file: path.to_string_lossy().into_owned(),
+                            output: String::new(),
+                            hands: Vec::new(),
+                            warnings: Vec::new(),
+                            error: Some(e),
+                        });
+                    }
+                }
+            }
+        }
+        debug!("stopped watching {}", input_dir.display());
+    });
+
+    Ok(DirWatcher {
+        watcher: Some(watcher),
+        thread: Some(thread),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{hand_json, scratch_dir, FOLDED};
+
+    #[test]
+    fn test_converts_only_appended_hands() {
+        let dir = scratch_dir("watch", "append");
+        let input = dir.join("session.ohh");
+        let out_dir = dir.join("out");
+        fs::create_dir_all(&out_dir).unwrap();
+        let options = ConvertOptions::default();
+        let second = hand_json(FOLDED, "g2");
+
+        // the second hand is still being written
+        fs::write(
+            &input,
+            format!("{}\n\n{}", hand_json(FOLDED, "g1"), &second[..40]),
+        )
+        .unwrap();
+        let mut cursor = Cursor::load(&out_dir);
+        let update = convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.hands, vec!["g1"]);
+
+        fs::write(
+            &input,
+            format!("{}\n\n{}\n", hand_json(FOLDED, "g1"), second),
+        )
+        .unwrap();
+        let update = convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.hands, vec!["g2"]);
+        assert!(convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .is_none());
+
+        // a restart reads the cursor back and finds nothing new
+        let mut restarted = Cursor::load(&out_dir);
+        assert_eq!(restarted, cursor);
+        assert_eq!(restarted.files.values().next().unwrap().hands, 2);
+        assert!(
+            convert_new_hands(&input, &out_dir, &mut restarted, &options)
+                .unwrap()
+                .is_none()
+        );
+
+        let text = fs::read_to_string(out_dir.join("session.ohh.pokerstars.txt")).unwrap();
+        assert_eq!(text.matches("PokerStars Hand #").count(), 2);
+        assert!(text.contains("Bob collected $0.15 from pot\n*** SUMMARY ***"));
+        assert!(text.contains("collected ($0.15)\n\n\n\nPokerStars Hand #g2"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bad_records_are_reported_once() {
+        let dir = scratch_dir("watch", "bad");
+        let input = dir.join("session.ohh");
+        let out_dir = dir.join("out");
+        fs::create_dir_all(&out_dir).unwrap();
+        let options = ConvertOptions::default();
+        let mut cursor = Cursor::default();
+        let bad = "{\"ohh\": {broken}\n";
+        let skipped = |update: &WatchUpdate| {
+            update
+                .warnings
+                .iter()
+                .filter(|w| w.contains("parse error"))
+                .count()
+        };
+
+        fs::write(&input, format!("{}\n{}", hand_json(FOLDED, "g1"), bad)).unwrap();
+        let update = convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.hands, vec!["g1"]);
+        assert_eq!(skipped(&update), 1);
+        assert!(convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .is_none());
+
+        let text = format!("{}\n{}{}\n", hand_json(FOLDED, "g1"), bad, hand_json(FOLDED, "g2"));
+        fs::write(&input, text).unwrap();
+        let update = convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.hands, vec!["g2"]);
+        assert_eq!(skipped(&update), 0);
+
+        // a file with nothing but bad records still moves on
+        let junk = dir.join("junk.ohh");
+        fs::write(&junk, bad.repeat(2)).unwrap();
+        let update = convert_new_hands(&junk, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert!(update.hands.is_empty() && update.error.is_some());
+        assert!(convert_new_hands(&junk, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replaced_file_starts_over() {
+        let dir = scratch_dir("watch", "replace");
+        let input = dir.join("session.ohh");
+        let out_dir = dir.join("out");
+        fs::create_dir_all(&out_dir).unwrap();
+        let options = ConvertOptions::default();
+        let mut cursor = Cursor::default();
+
+        let two = format!(
+            "{}\n{}\n",
+            hand_json(FOLDED, "a1"),
+            hand_json(FOLDED, "a2")
+        );
+        fs::write(&input, two).unwrap();
+        convert_new_hands(&input, &out_dir, &mut cursor, &options).unwrap();
+
+        fs::write(&input, hand_json(FOLDED, "b1")).unwrap();
+        let update = convert_new_hands(&input, &out_dir, &mut cursor, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.hands, vec!["b1"]);
+
+        let text = fs::read_to_string(out_dir.join("session.ohh.pokerstars.txt")).unwrap();
+        assert!(text.starts_with("PokerStars Hand #b1"));
+        assert!(!text.contains("#a1"));
+
+        // the same stem with another extension has an output of its own
+        let json = dir.join("session.json");
+        fs::write(&json, hand_json(FOLDED, "c1")).unwrap();
+        convert_new_hands(&json, &out_dir, &mut cursor, &options).unwrap();
+        let text = fs::read_to_string(out_dir.join("session.json.pokerstars.txt")).unwrap();
+        assert!(text.starts_with("PokerStars Hand #c1"));
+        let text = fs::read_to_string(out_dir.join("session.ohh.pokerstars.txt")).unwrap();
+        assert!(text.starts_with("PokerStars Hand #b1"));
+
+        let auto = ConvertOptions {
+            hero: Some("auto".to_string()),
+            ..Default::default()
+        };
+        assert!(watch_dir(&dir, &out_dir, auto, |_| {}).is_err());
+
+        assert!(is_ohh_file(Path::new("x/hands.ohh.txt")));
+        assert!(!is_ohh_file(Path::new("x/hands.pokerstars.txt")));
+        assert!(!is_ohh_file(Path::new(CURSOR_FILE)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
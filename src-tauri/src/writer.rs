@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::converter::{
+    act_line_with_context, amount, board_cards, build_header, build_seats, build_table, cards,
+    collect_lines, describe_hand, game_name, is_tournament, limit_name, name_by_id, pot_label,
+    seat_summary_line, sorted_pots, street_header, total_pot_line, Action, OhhHand, Player,
+};
+use crate::money::Money;
+
+// one text dialect of hand history. the defaults produce pokerstars text,
+// other sites override the lines that differ
+pub trait HandHistoryWriter {
+    fn header(&self, h: &OhhHand) -> String;
+
+    fn table(&self, h: &OhhHand) -> String {
+        build_table(h)
+    }
+
+    fn seats(&self, h: &OhhHand) -> String {
+        build_seats(h)
+    }
+
+    // printed after the blinds, right before the hole cards
+    fn before_hole_cards(&self, _h: &OhhHand) -> Option<String> {
+        None
+    }
+
+    fn street_header(&self, street: &str, brd: &[String], all_board: &[String]) -> String {
+        street_header(street, brd, all_board)
+    }
+
+    fn action_line(
+        &self,
+        h: &OhhHand,
+        a: &Action,
+        tracker: &HashMap<u32, (Money, Money, bool)>,
+    ) -> Option<String> {
+        act_line_with_context(h, a, tracker)
+    }
+
+    fn uncalled_line(&self, h: &OhhHand, player_id: &str, returned: Money) -> String {
+        format!(
+            "Uncalled bet ({}) returned to {}",
+            amount(h, returned),
+            name_by_id(h, player_id)
+        )
+    }
+
+    fn collect_lines(&self, h: &OhhHand) -> Vec<String> {
+        collect_lines(h)
+    }
+
+    fn total_pot_line(&self, h: &OhhHand) -> String {
+        total_pot_line(h)
+    }
+
+    fn board_line(&self, board: &[String]) -> String {
+        format!("Board [{}]", cards(board))
+    }
+
+    fn seat_summary_line(&self, h: &OhhHand, p: &Player, board: &[String]) -> String {
+        seat_summary_line(h, p, board)
+    }
+
+    fn summary(&self, h: &OhhHand) -> String {
+        let board = board_cards(h);
+
+        let mut lines = vec!["*** SUMMARY ***".to_string()];
+        lines.push(self.total_pot_line(h));
+
+        if !board.is_empty() {
+            lines.push(self.board_line(&board));
+        }
+
+        let mut players = h.players.clone();
+        players.sort_by_key(|p| p.seat);
+
+        for p in &players {
+            lines.push(self.seat_summary_line(h, p, &board));
+        }
+
+        lines.join("\n")
+    }
+
+    fn separator(&self) -> &'static str {
+        "\n\n\n\n"
+    }
+}
+
+pub struct PokerStarsWriter;
+
+impl HandHistoryWriter for PokerStarsWriter {
+    fn header(&self, h: &OhhHand) -> String {
+        build_header(h)
+    }
+}
+
+// "2023-12-05T02:50:49.886Z" as date and time, without fractions or zone
+fn date_time(h: &OhhHand) -> (String, String) {
+    let ts = h
+        .start_date_utc
+        .split('.')
+        .next()
+        .unwrap_or(&h.start_date_utc)
+        .trim_end_matches('Z');
+    let (date, time) = ts.split_once('T').unwrap_or((ts, ""));
+    (date.replace('-', "/"), time.to_string())
+}
+
+fn tournament_number(h: &OhhHand) -> String {
+    h.tournament_info
+        .as_ref()
+        .and_then(|t| t.tournament_number.clone())
+        .unwrap_or_else(|| "0".to_string())
+}
+
+pub struct GgPokerWriter;
+
+impl HandHistoryWriter for GgPokerWriter {
+    fn header(&self, h: &OhhHand) -> String {
+        let (date, time) = date_time(h);
+        let blinds = format!(
+            "({}/{})",
+            amount(h, h.small_blind_amount),
+            amount(h, h.big_blind_amount)
+        );
+
+        if is_tournament(h) {
+            let level = h
+                .tournament_info
+                .as_ref()
+                .and_then(|t| t.level)
+                .map(|l| format!(" - Level{}", l))
+                .unwrap_or_default();
+            let name = h
+                .tournament_info
+                .as_ref()
+                .and_then(|t| t.name.clone())
+                .map(|n| format!(" {}", n))
+                .unwrap_or_default();
+            return format!(
+                "Poker Hand #{}: Tournament #{},{} {} {}{}{} - {} {}",
+                h.game_number,
+                tournament_number(h),
+                name,
+                game_name(h),
+                limit_name(h),
+                level,
+                blinds,
+                date,
+                time
+            );
+        }
+
+        format!(
+            "Poker Hand #{}: {} {} {} - {} {}",
+            h.game_number,
+            game_name(h),
+            limit_name(h),
+            blinds,
+            date,
+            time
+        )
+    }
+
+    fn street_header(&self, street: &str, brd: &[String], all_board: &[String]) -> String {
+        match street {
+            "Showdown" => "*** SHOWDOWN ***".to_string(),
+            _ => street_header(street, brd, all_board),
+        }
+    }
+
+    fn total_pot_line(&self, h: &OhhHand) -> String {
+        let jackpot: Money = h.pots.iter().filter_map(|p| p.jackpot).sum();
+        let line = total_pot_line(h);
+        let (pots, rake) = line.split_once(" | ").unwrap_or((&line, ""));
+        format!(
+            "{} | {} | Jackpot {} | Bingo {} | Fortune {} | Tax {}",
+            pots,
+            rake,
+            amount(h, jackpot),
+            amount(h, Money::ZERO),
+            amount(h, Money::ZERO),
+            amount(h, Money::ZERO)
+        )
+    }
+}
+
+pub struct FullTiltWriter;
+
+impl FullTiltWriter {
+    fn pot_name(h: &OhhHand, idx: usize) -> String {
+        match pot_label(h, idx).as_str() {
+            "pot" => "the pot".to_string(),
+            "main pot" => "the main pot".to_string(),
+            "side pot" => "the side pot".to_string(),
+            _ => format!("side pot #{}", idx),
+        }
+    }
+}
+
+impl HandHistoryWriter for FullTiltWriter {
+    fn header(&self, h: &OhhHand) -> String {
+        let (date, time) = date_time(h);
+        let table = if is_tournament(h) {
+            let name = h
+                .tournament_info
+                .as_ref()
+                .and_then(|t| t.name.clone())
+                .unwrap_or_else(|| "Tournament".to_string());
+            format!(
+                "{} ({}), Table {}",
+                name,
+                tournament_number(h),
+                h.table_name
+            )
+        } else {
+            format!("Table {} ({} max)", h.table_name, h.table_size)
+        };
+
+        format!(
+            "Full Tilt Poker Game #{}: {} - {}/{} - {} {} - {} UTC - {}",
+            h.game_number,
+            table,
+            amount(h, h.small_blind_amount),
+            amount(h, h.big_blind_amount),
+            limit_name(h),
+            game_name(h),
+            time,
+            date
+        )
+    }
+
+    // the table is part of the header and the button follows the blinds
+    fn table(&self, _h: &OhhHand) -> String {
+        String::new()
+    }
+
+    fn seats(&self, h: &OhhHand) -> String {
+        let mut players = h.players.clone();
+        players.sort_by_key(|p| p.seat);
+
+        players
+            .iter()
+            .map(|p| {
+                format!(
+                    "Seat {}: {} ({})",
+                    p.seat,
+                    p.name,
+                    amount(h, p.starting_stack)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn before_hole_cards(&self, h: &OhhHand) -> Option<String> {
+        Some(format!("The button is in seat #{}", h.dealer_seat))
+    }
+
+    fn action_line(
+        &self,
+        h: &OhhHand,
+        a: &Action,
+        tracker: &HashMap<u32, (Money, Money, bool)>,
+    ) -> Option<String> {
+        let n = name_by_id(h, a.player_id.as_ref()?);
+        let amt = a.amount.unwrap_or_default();
+        let allin = if a.is_allin.unwrap_or(false) {
+            ", and is all in"
+        } else {
+            ""
+        };
+
+        match a.action.as_str() {
+            "Post SB" => Some(format!("{} posts the small blind of {}", n, amount(h, amt))),
+            "Post BB" => Some(format!("{} posts the big blind of {}", n, amount(h, amt))),
+            "Post Ante" => Some(format!("{} antes {}", n, amount(h, amt))),
+            "Post Dead" | "Post Extra Blind" => Some(format!("{} posts {}", n, amount(h, amt))),
+            "Straddle" => Some(format!("{} straddles {}", n, amount(h, amt))),
+            "Dealt Cards" => act_line_with_context(h, a, tracker),
+            "Fold" => Some(format!("{} folds", n)),
+            "Check" => Some(format!("{} checks", n)),
+            "Call" => Some(format!("{} calls {}{}", n, amount(h, amt), allin)),
+            "Bet" | "Raise" => match tracker.get(&a.action_number) {
+                Some((prev_bet, total, has_bet))
+                    if prev_bet.is_positive() && (a.action == "Raise" || *has_bet) =>
+                {
+                    Some(format!("{} raises to {}{}", n, amount(h, *total), allin))
+                }
+                _ => Some(format!("{} bets {}{}", n, amount(h, amt), allin)),
+            },
+            "Shows Cards" => {
+                let hole = a.cards.as_ref().filter(|c| c.len() >= 2)?;
+                let board: Vec<String> = board_cards(h).into_iter().take(5).collect();
+                let desc = describe_hand(h, hole, &board)
+                    .map(|d| format!(" {}", d))
+                    .unwrap_or_default();
+                Some(format!("{} shows [{}]{}", n, cards(hole), desc))
+            }
+            "Muck" => Some(format!("{} mucks", n)),
+            _ => None,
+        }
+    }
+
+    fn uncalled_line(&self, h: &OhhHand, player_id: &str, returned: Money) -> String {
+        format!(
+            "Uncalled bet of {} returned to {}",
+            amount(h, returned),
+            name_by_id(h, player_id)
+        )
+    }
+
+    fn collect_lines(&self, h: &OhhHand) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (idx, pot) in sorted_pots(h).iter().enumerate().rev() {
+            for w in &pot.player_wins {
+                lines.push(format!(
+                    "{} wins {} ({})",
+                    name_by_id(h, &w.player_id),
+                    Self::pot_name(h, idx),
+                    amount(h, w.win_amount)
+                ));
+            }
+        }
+        lines
+    }
+
+    fn board_line(&self, board: &[String]) -> String {
+        format!("Board: [{}]", cards(board))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    #[default]
+    PokerStars,
+    GgPoker,
+    FullTilt,
+}
+
+impl Dialect {
+    pub fn writer(self) -> &'static dyn HandHistoryWriter {
+        match self {
+            Dialect::PokerStars => &PokerStarsWriter,
+            Dialect::GgPoker => &GgPokerWriter,
+            Dialect::FullTilt => &FullTiltWriter,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::PokerStars => "pokerstars",
+            Dialect::GgPoker => "ggpoker",
+            Dialect::FullTilt => "fulltilt",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Dialect> {
+        match name.to_lowercase().replace([' ', '-', '_'], "").as_str() {
+            "pokerstars" | "ps" => Some(Dialect::PokerStars),
+            "ggpoker" | "gg" => Some(Dialect::GgPoker),
+            "fulltilt" | "ft" => Some(Dialect::FullTilt),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::write_hand;
+    use crate::fixtures::PLAYED;
+
+    fn hand() -> OhhHand {
+        crate::fixtures::hand(PLAYED, "w1")
+    }
+
+    #[test]
+    fn test_ggpoker_dialect() {
+        let (text, _) = write_hand(&hand(), Dialect::GgPoker.writer());
+        assert!(text.starts_with(
+            "Poker Hand #w1: Hold'em No Limit ($0.05/$0.10) - 2023/12/05 02:50:49\n\
+             Table 'Alcyone' 6-max Seat #1 is the button"
+        ));
+        assert!(text.contains("Ann: raises $0.20 to $0.30"));
+        assert!(text.contains(
+            "Total pot $0.60 | Rake $0.02 | Jackpot $0.00 | Bingo $0.00 | Fortune $0.00 | Tax $0.00"
+        ));
+    }
+
+    #[test]
+    fn test_full_tilt_dialect() {
+        let (text, _) = write_hand(&hand(), Dialect::FullTilt.writer());
+        assert!(text.starts_with(
+            "Full Tilt Poker Game #w1: Table Alcyone (6 max) - $0.05/$0.10 - No Limit Hold'em - 02:50:49 UTC - 2023/12/05\n\
+             Seat 1: Ann ($10.00)\nSeat 2: Bob ($10.00)\n\
+             Ann posts the small blind of $0.05\nBob posts the big blind of $0.10\n\
+             The button is in seat #1\n*** HOLE CARDS ***\n\
+             Ann raises to $0.30\nBob calls $0.20\n*** FLOP *** [Ah Kd 2c]\n\
+             Bob bets $0.50\nAnn folds\nUncalled bet of $0.50 returned to Bob\n\
+             Bob wins the pot ($0.58)\n*** SUMMARY ***"
+        ));
+        assert!(text.contains("Board: [Ah Kd 2c]"));
+    }
+
+    #[test]
+    fn test_dialect_names() {
+        assert_eq!(Dialect::parse("GGPoker"), Some(Dialect::GgPoker));
+        assert_eq!(Dialect::parse("full-tilt"), Some(Dialect::FullTilt));
+        assert_eq!(Dialect::parse("ipoker"), None);
+        assert_eq!(
+            serde_json::from_str::<Dialect>("\"fulltilt\"").unwrap(),
+            Dialect::FullTilt
+        );
+        let (text, _) = write_hand(&hand(), Dialect::PokerStars.writer());
+        assert_eq!(text, crate::converter::ohh_to_pokerstars_text(&hand()));
+    }
+}
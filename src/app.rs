@@ -1,19 +1,261 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{Event, FileReader, HtmlInputElement, DragEvent};
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "dialog"], js_name = open, catch)]
+    async fn open_dialog(options: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct ConvertOptions {
+    strict: bool,
+    dialect: String,
+    hero: Option<String>,
+    anonymize: Option<AnonymizeOptions>,
+    names: String,
+    aliases: HashMap<String, String>,
+    filter: Option<String>,
+    split: Option<SplitOptions>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SplitOptions {
+    by: Option<String>,
+    max_hands: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+// mirrors split::OutputPart from the backend
+#[derive(Clone, Deserialize)]
+struct OutputPart {
+    key: String,
+    output: String,
+}
+
+#[derive(Serialize)]
+struct ZipEntry {
+    name: String,
+    output: String,
+}
+
+#[derive(Serialize)]
+struct ZipArgs {
+    files: Vec<ZipEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AliasArgs {
+    file_path: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnonymizeOptions {
+    style: String,
+    salt: Option<String>,
+    keep_hero: bool,
 }
 
 #[derive(Serialize, Deserialize)]
 struct ConvertArgs {
     content: String,
+    options: ConvertOptions,
+}
+
+// mirrors filter::FilterCount from the backend
+#[derive(Clone, Copy, Deserialize)]
+struct FilterCount {
+    total: usize,
+    matched: usize,
+}
+
+// mirrors diagnostics::ConvertReport from the backend
+#[derive(Clone, Default, Deserialize)]
+struct ConvertReport {
+    output: String,
+    hands: Vec<String>,
+    warnings: Vec<String>,
+    #[serde(default)]
+    parts: Vec<OutputPart>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchArgs {
+    input_dir: String,
+    output_dir: String,
+    options: ConvertOptions,
+}
+
+// mirrors watch::WatchUpdate from the backend
+#[derive(Clone, Deserialize)]
+struct WatchUpdate {
+    file: String,
+    output: String,
+    hands: Vec<String>,
+    warnings: Vec<String>,
+    error: Option<String>,
+}
+
+impl WatchUpdate {
+    fn summary(&self) -> String {
+        match &self.error {
+            Some(e) => format!("[ERR] {}: {}", self.file, e),
+            None if self.warnings.is_empty() => {
+                format!("{}: {} new hands -> {}", self.file, self.hands.len(), self.output)
+            }
+            None => format!(
+                "{}: {} new hands with {} warnings -> {}",
+                self.file,
+                self.hands.len(),
+                self.warnings.len(),
+                self.output
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchOptions {
+    combine: bool,
+    output_dir: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BatchArgs {
+    paths: Vec<String>,
+    options: ConvertOptions,
+    batch: BatchOptions,
+}
+
+// mirrors batch::BatchProgress from the backend
+#[derive(Clone, Deserialize)]
+struct BatchProgress {
+    done: usize,
+    total: usize,
+}
+
+// mirrors batch::FileResult and batch::BatchSummary from the backend
+#[derive(Clone, Deserialize)]
+struct FileResult {
+    file: String,
+    outputs: Vec<String>,
+    hands: usize,
+    warnings: Vec<String>,
+    error: Option<String>,
+}
+
+impl FileResult {
+    fn summary(&self) -> String {
+        let target = if self.outputs.is_empty() {
+            String::new()
+        } else {
+            format!(" -> {}", self.outputs.join(", "))
+        };
+        match &self.error {
+            Some(e) => format!("[ERR] {}: {}", self.file, e),
+            None if self.warnings.is_empty() => format!("{}: {} hands{}", self.file, self.hands, target),
+            None => format!(
+                "{}: {} hands with {} warnings{}",
+                self.file,
+                self.hands,
+                self.warnings.len(),
+                target
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct BatchSummary {
+    files: Vec<FileResult>,
+    hands: usize,
+    failed: usize,
+    outputs: Vec<String>,
+    output: String,
+    parts: Vec<OutputPart>,
+    duplicates: Vec<String>,
+}
+
+// asks the native dialog for paths; a cancelled dialog gives nothing
+async fn pick_paths(directory: bool, multiple: bool) -> Vec<String> {
+    let options = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&options, &"directory".into(), &directory.into());
+    let _ = js_sys::Reflect::set(&options, &"multiple".into(), &multiple.into());
+    match open_dialog(options.into()).await {
+        Ok(value) if js_sys::Array::is_array(&value) => js_sys::Array::from(&value)
+            .iter()
+            .filter_map(|v| v.as_string())
+            .collect(),
+        Ok(value) => value.as_string().into_iter().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+// "converted_hands.txt" and part "Zurich_002" -> "converted_hands_Zurich_002.txt",
+// like split::part_file_name on the backend
+fn part_name(file_name: &str, key: &str) -> String {
+    match file_name.split_once('.') {
+        Some((stem, extension)) => format!("{}_{}.{}", stem, key, extension),
+        None => format!("{}_{}", file_name, key),
+    }
+}
+
+// an empty field or zero means no limit
+fn limit(text: &str) -> Option<usize> {
+    text.trim().parse().ok().filter(|n| *n > 0)
+}
+
+// offers `blob` as a download through a temporary link
+fn save_blob(blob: &web_sys::Blob, file_name: &str) -> Result<(), String> {
+    let window = web_sys::window().ok_or("No window object available")?;
+    let document = window
+        .document()
+        .ok_or("No document available")?;
+
+    let url = web_sys::Url::create_object_url_with_blob(blob)
+        .map_err(|_| "Failed to create download URL".to_string())?;
+
+    let a = document
+        .create_element("a")
+        .map_err(|_| "Failed to create link element".to_string())?;
+
+    a.set_attribute("href", &url)
+        .map_err(|_| "Failed to set href".to_string())?;
+    a.set_attribute("download", file_name)
+        .map_err(|_| "Failed to set download attribute".to_string())?;
+
+    let html_element = a
+        .dyn_into::<web_sys::HtmlElement>()
+        .map_err(|_| "Failed to convert to HtmlElement".to_string())?;
+
+    html_element.click();
+
+    web_sys::Url::revoke_object_url(&url)
+        .map_err(|_| "Failed to revoke URL".to_string())?;
+
+    Ok(())
+}
+
+// an empty hero field leaves the hero the file names, if any
+fn hero_option(hero: &str) -> Option<String> {
+    let hero = hero.trim();
+    (!hero.is_empty()).then(|| hero.to_string())
 }
 
 #[derive(Clone, Default)]
@@ -29,9 +271,221 @@ pub fn App() -> impl IntoView {
     let (upload_status, set_upload_status) = signal::<Option<String>>(None);
     let (is_converting, set_is_converting) = signal(false);
     let (converted_content, set_converted_content) = signal(String::new());
+    let (warnings, set_warnings) = signal::<Vec<String>>(Vec::new());
+    let (strict, set_strict) = signal(false);
+    let (dialect, set_dialect) = signal("pokerstars".to_string());
+    let (hero, set_hero) = signal(String::new());
+    let (anonymize, set_anonymize) = signal(false);
+    let (pseudonyms, set_pseudonyms) = signal("sequential".to_string());
+    let (salt, set_salt) = signal(String::new());
+    let (keep_hero, set_keep_hero) = signal(false);
+    let (names, set_names) = signal("name".to_string());
+    let (aliases, set_aliases) = signal(HashMap::<String, String>::new());
+    let (alias_status, set_alias_status) = signal::<Option<String>>(None);
+    let (filter, set_filter) = signal(String::new());
+    let (filter_status, set_filter_status) = signal::<Option<String>>(None);
+    let (split_by, set_split_by) = signal(String::new());
+    let (max_hands, set_max_hands) = signal(String::new());
+    let (max_mb, set_max_mb) = signal(String::new());
+    let (parts, set_parts) = signal::<Vec<OutputPart>>(Vec::new());
+
+    // ohh output is picked by command, so every request names a text dialect
+    let convert_options = move || ConvertOptions {
+        strict: strict.get_untracked(),
+        dialect: match dialect.get_untracked().as_str() {
+            "ohh" => "pokerstars".to_string(),
+            other => other.to_string(),
+        },
+        hero: hero_option(&hero.get_untracked()),
+        anonymize: anonymize.get_untracked().then(|| AnonymizeOptions {
+            style: pseudonyms.get_untracked(),
+            salt: Some(salt.get_untracked()).filter(|s| !s.is_empty()),
+            keep_hero: keep_hero.get_untracked(),
+        }),
+        names: names.get_untracked(),
+        aliases: aliases.get_untracked(),
+        filter: Some(filter.get_untracked().trim().to_string()).filter(|f| !f.is_empty()),
+        split: Some(SplitOptions {
+            by: Some(split_by.get_untracked()).filter(|b| !b.is_empty()),
+            max_hands: limit(&max_hands.get_untracked()),
+            max_bytes: limit(&max_mb.get_untracked()).map(|mb| mb * 1024 * 1024),
+        })
+        .filter(|s| *s != SplitOptions { by: None, max_hands: None, max_bytes: None }),
+    };
+
+    let pick_aliases = move |_| {
+        spawn_local(async move {
+            let Some(file_path) = pick_paths(false, false).await.into_iter().next() else {
+                return;
+            };
+            let Ok(args) = serde_wasm_bindgen::to_value(&AliasArgs { file_path }) else {
+                return;
+            };
+            match invoke("load_aliases", args).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<HashMap<String, String>>(value) {
+                    Ok(map) => {
+                        set_alias_status.set(Some(format!("{} aliases", map.len())));
+                        set_aliases.set(map);
+                    }
+                    Err(_) => set_alias_status.set(Some("[ERR] Invalid response from backend".to_string())),
+                },
+                Err(e) => {
+                    let message = e.as_string().unwrap_or_else(|| "failed to load aliases".to_string());
+                    set_alias_status.set(Some(format!("[ERR] {}", message)));
+                    set_aliases.set(HashMap::new());
+                }
+            }
+        });
+    };
+    let (output_name, set_output_name) = signal("converted_hands.txt".to_string());
+
+    let (watch_input, set_watch_input) = signal(String::new());
+    let (watch_output, set_watch_output) = signal(String::new());
+    let (is_watching, set_is_watching) = signal(false);
+    let (watch_log, set_watch_log) = signal::<Vec<String>>(Vec::new());
+
+    let (batch_paths, set_batch_paths) = signal::<Vec<String>>(Vec::new());
+    let (batch_output, set_batch_output) = signal::<Option<String>>(None);
+    let (batch_combine, set_batch_combine) = signal(false);
+    let (batch_progress, set_batch_progress) = signal::<Option<(usize, usize)>>(None);
+    let (batch_log, set_batch_log) = signal::<Vec<String>>(Vec::new());
 
     let file_input_ref = NodeRef::<leptos::html::Input>::new();
 
+    // the backend reports every file the folder watch converts
+    spawn_local(async move {
+        let handler = Closure::new(move |event: JsValue| {
+            let payload = js_sys::Reflect::get(&event, &JsValue::from_str("payload"))
+                .unwrap_or(JsValue::NULL);
+            if let Ok(update) = serde_wasm_bindgen::from_value::<WatchUpdate>(payload) {
+                set_watch_log.update(|log| {
+                    log.insert(0, update.summary());
+                    log.truncate(50);
+                });
+            }
+        });
+        listen("watch-update", &handler).await;
+        handler.forget();
+    });
+
+    spawn_local(async move {
+        let handler = Closure::new(move |event: JsValue| {
+            let payload = js_sys::Reflect::get(&event, &JsValue::from_str("payload"))
+                .unwrap_or(JsValue::NULL);
+            if let Ok(progress) = serde_wasm_bindgen::from_value::<BatchProgress>(payload) {
+                set_batch_progress.set(Some((progress.done, progress.total)));
+            }
+        });
+        listen("batch-progress", &handler).await;
+        handler.forget();
+    });
+
+    let pick_batch_files = move |_| {
+        spawn_local(async move {
+            let paths = pick_paths(false, true).await;
+            if !paths.is_empty() {
+                set_batch_paths.set(paths);
+            }
+        });
+    };
+
+    let pick_batch_folder = move |_| {
+        spawn_local(async move {
+            let paths = pick_paths(true, false).await;
+            if !paths.is_empty() {
+                set_batch_paths.set(paths);
+            }
+        });
+    };
+
+    let pick_batch_output = move |_| {
+        spawn_local(async move {
+            if let Some(dir) = pick_paths(true, false).await.into_iter().next() {
+                set_batch_output.set(Some(dir));
+            }
+        });
+    };
+
+    let run_batch = move |_| {
+        let args = BatchArgs {
+            paths: batch_paths.get_untracked(),
+            options: convert_options(),
+            batch: BatchOptions {
+                combine: batch_combine.get_untracked(),
+                output_dir: batch_output.get_untracked(),
+            },
+        };
+        let Ok(args) = serde_wasm_bindgen::to_value(&args) else {
+            return;
+        };
+
+        set_is_converting.set(true);
+        set_batch_progress.set(Some((0, 0)));
+        set_batch_log.set(Vec::new());
+        spawn_local(async move {
+            let result = invoke("convert_batch", args).await;
+            set_is_converting.set(false);
+            set_batch_progress.set(None);
+
+            match result.map(serde_wasm_bindgen::from_value::<BatchSummary>) {
+                Ok(Ok(summary)) => {
+                    let mut log = vec![format!(
+                        "[OK] Converted {} hands from {} files, {} failed",
+                        summary.hands,
+                        summary.files.len(),
+                        summary.failed
+                    )];
+                    log.extend(summary.files.iter().map(FileResult::summary));
+                    log.extend(summary.duplicates);
+                    log.extend(summary.outputs.iter().map(|o| format!("wrote {}", o)));
+                    set_batch_log.set(log);
+                    if !summary.output.is_empty() {
+                        set_output_name.set("converted_hands.txt".to_string());
+                        set_converted_content.set(summary.output);
+                        set_parts.set(summary.parts);
+                        set_warnings.set(Vec::new());
+                    }
+                }
+                Ok(Err(_)) => set_batch_log.set(vec!["[ERR] Invalid response from backend".to_string()]),
+                Err(e) => {
+                    let message = e.as_string().unwrap_or_else(|| "batch conversion failed".to_string());
+                    set_batch_log.set(vec![format!("[ERR] {}", message)]);
+                }
+            }
+        });
+    };
+
+    let toggle_watch = move |_| {
+        let set_watch_log_clone = set_watch_log;
+        let set_is_watching_clone = set_is_watching;
+
+        if is_watching.get_untracked() {
+            spawn_local(async move {
+                let _ = invoke("stop_watch", js_sys::Object::new().into()).await;
+                set_is_watching_clone.set(false);
+            });
+            return;
+        }
+
+        let args = WatchArgs {
+            input_dir: watch_input.get_untracked(),
+            output_dir: watch_output.get_untracked(),
+            options: convert_options(),
+        };
+        spawn_local(async move {
+            let Ok(args) = serde_wasm_bindgen::to_value(&args) else {
+                return;
+            };
+            match invoke("start_watch", args).await {
+                Ok(_) => set_is_watching_clone.set(true),
+                Err(e) => set_watch_log_clone.update(|log| {
+                    let message = e.as_string().unwrap_or_else(|| "failed to start watching".to_string());
+                    log.insert(0, format!("[ERR] {}", message));
+                }),
+            }
+        });
+    };
+
     let handle_files = move |files: web_sys::FileList| {
         if files.length() > 0 {
             if let Some(file) = files.item(0) {
@@ -62,7 +516,7 @@ pub fn App() -> impl IntoView {
 
                         let onload = Closure::wrap(Box::new(move |_: Event| {
                             if let Ok(result) = fr.result() {
-                                if let Some(text) = result.as_string() {
+                                if result.as_string().is_some() {
                                     set_upload_status.set(Some("File ready to convert".to_string()));
                                 }
                             }
@@ -114,6 +568,46 @@ pub fn App() -> impl IntoView {
         set_is_dragging.set(false);
     };
 
+    // counts the hands of the chosen file the filter keeps, without converting
+    let count_filter = move |_| {
+        let Some(file) = file_input_ref
+            .get()
+            .and_then(|input| input.files())
+            .and_then(|files| files.item(0))
+        else {
+            set_filter_status.set(Some("Choose a file first".to_string()));
+            return;
+        };
+        let options = convert_options();
+        set_filter_status.set(Some("Counting...".to_string()));
+        spawn_local(async move {
+            let Some(content) = wasm_bindgen_futures::JsFuture::from(file.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+            else {
+                set_filter_status.set(Some("[ERR] Failed to read file".to_string()));
+                return;
+            };
+            let Ok(args) = serde_wasm_bindgen::to_value(&ConvertArgs { content, options }) else {
+                return;
+            };
+            match invoke("count_matches", args).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<FilterCount>(value) {
+                    Ok(count) => set_filter_status.set(Some(format!(
+                        "{} of {} hands match",
+                        count.matched, count.total
+                    ))),
+                    Err(_) => set_filter_status.set(Some("[ERR] Invalid response from backend".to_string())),
+                },
+                Err(e) => {
+                    let message = e.as_string().unwrap_or_else(|| "counting failed".to_string());
+                    set_filter_status.set(Some(format!("[ERR] {}", message)));
+                }
+            }
+        });
+    };
+
     let convert_file = move |_| {
         if let Some(input_element) = file_input_ref.get() {
             let input_el: &HtmlInputElement = input_element.as_ref();
@@ -128,6 +622,10 @@ pub fn App() -> impl IntoView {
                     let set_upload_status_clone = set_upload_status;
                     let set_converted_content_clone = set_converted_content;
                     let set_selected_file_info_clone = set_selected_file_info;
+                    let set_warnings_clone = set_warnings;
+                    let dialect_name = dialect.get_untracked();
+                    let options = convert_options();
+                    let set_output_name_clone = set_output_name;
 
                     spawn_local(async move {
                         // Use web_sys Blob reader to read the file
@@ -135,41 +633,66 @@ pub fn App() -> impl IntoView {
                         {
                             Ok(text_promise) => {
                                 if let Some(content) = text_promise.as_string() {
-                                    match serde_wasm_bindgen::to_value(&ConvertArgs { content }) {
-                                        Ok(args) => {
-                                            match invoke::<_, String>("convert_ohh_content", args).await {
-                                                Ok(_) => {
-                                                    set_upload_status_clone.set(Some("conversion completed successfully.".to_string()));
-                                                    set_is_converting_clone.set(false);
-                                                }
-                                                Err(e) => {
-                                                    set_upload_status_clone.set(Some(format!("error: {}", e)));
-                                                    set_is_converting_clone.set(false);
-                                                }
-                                            }
+                                    // pokerstars text goes the other way, back to ohh
+                                    let reverse = content
+                                        .trim_start_matches('\u{feff}')
+                                        .trim_start()
+                                        .starts_with("PokerStars");
+                                    let (command, output_name) = if reverse {
+                                        ("convert_pokerstars_content", "converted_hands.ohh")
+                                    } else if dialect_name == "ohh" {
+                                        ("normalize_ohh_content", "normalized_hands.ohh")
+                                    } else {
+                                        ("convert_ohh_content", "converted_hands.txt")
+                                    };
+                                    set_output_name_clone.set(output_name.to_string());
 
+                                    match serde_wasm_bindgen::to_value(&ConvertArgs {
+                                        content,
+                                        options,
+                                    }) {
+                                        Ok(args) => {
+                                            let result = invoke(command, args).await;
+                                            set_is_converting_clone.set(false);
 
-                                            if let Some(response) = result.as_string() {
-                                                if response.starts_with("Error")
-                                                    || response.starts_with("Failed")
-                                                {
-                                                    set_upload_status_clone.set(Some(format!(
-                                                        "[ERR] {}",
-                                                        response
-                                                    )));
+                                            match result {
+                                                Ok(value) => match serde_wasm_bindgen::from_value::<ConvertReport>(value) {
+                                                    Ok(report) => {
+                                                        let status = if report.warnings.is_empty() {
+                                                            format!(
+                                                                "[OK] Successfully converted {} hands: {}",
+                                                                report.hands.len(),
+                                                                file_name
+                                                            )
+                                                        } else {
+                                                            format!(
+                                                                "[OK] Converted {} hands with {} warnings: {}",
+                                                                report.hands.len(),
+                                                                report.warnings.len(),
+                                                                file_name
+                                                            )
+                                                        };
+                                                        set_converted_content_clone.set(report.output);
+                                                        set_parts.set(report.parts);
+                                                        set_warnings_clone.set(report.warnings);
+                                                        set_upload_status_clone.set(Some(status));
+                                                        set_selected_file_info_clone.set(None);
+                                                    }
+                                                    Err(_) => {
+                                                        set_upload_status_clone.set(Some(
+                                                            "[ERR] Invalid response from backend".to_string(),
+                                                        ));
+                                                    }
+                                                },
+                                                Err(e) => {
+                                                    let message = e
+                                                        .as_string()
+                                                        .unwrap_or_else(|| "conversion failed".to_string());
+                                                    set_upload_status_clone.set(Some(format!("[ERR] {}", message)));
                                                     set_converted_content_clone.set(String::new());
-                                                } else {
-                                                    set_converted_content_clone.set(response);
-                                                    set_upload_status_clone.set(Some(format!(
-                                                        "[OK] Successfully converted: {}",
-                                                        file_name
-                                                    )));
-                                                    set_selected_file_info_clone.set(None);
+                                                    set_parts.set(Vec::new());
+                                                    set_warnings_clone.set(Vec::new());
                                                 }
-                                            } else {
-                                                set_upload_status_clone.set(Some(
-                                                    "[ERR] Invalid response from backend".to_string(),
-                                                ));
                                             }
                                         }
                                         Err(e) => {
@@ -205,43 +728,55 @@ pub fn App() -> impl IntoView {
         if content.is_empty() {
             return;
         }
+        let file_name = output_name.get_untracked();
 
         // Create a blob and download link
         spawn_local(async move {
-            let result: Result<(), String> = (|| {
-                let window = web_sys::window().ok_or("No window object available")?;
-                let document = window
-                    .document()
-                    .ok_or("No document available")?;
-
-                let array = js_sys::Array::new();
-                array.push(&JsValue::from_str(&content));
-                let blob = web_sys::Blob::new_with_str_sequence(&array)
-                    .map_err(|_| "Failed to create blob".to_string())?;
-
-                let url = web_sys::Url::create_object_url_with_blob(&blob)
-                    .map_err(|_| "Failed to create download URL".to_string())?;
-
-                let a = document
-                    .create_element("a")
-                    .map_err(|_| "Failed to create link element".to_string())?;
-
-                a.set_attribute("href", &url)
-                    .map_err(|_| "Failed to set href".to_string())?;
-                a.set_attribute("download", "converted_hands.txt")
-                    .map_err(|_| "Failed to set download attribute".to_string())?;
-
-                let html_element = a
-                    .dyn_into::<web_sys::HtmlElement>()
-                    .map_err(|_| "Failed to convert to HtmlElement".to_string())?;
+            let array = js_sys::Array::new();
+            array.push(&JsValue::from_str(&content));
+            let result = web_sys::Blob::new_with_str_sequence(&array)
+                .map_err(|_| "Failed to create blob".to_string())
+                .and_then(|blob| save_blob(&blob, &file_name));
 
-                html_element.click();
+            if let Err(e) = result {
+                set_upload_status.set(Some(format!("[ERR] Download failed: {}", e)));
+            }
+        });
+    };
 
-                web_sys::Url::revoke_object_url(&url)
-                    .map_err(|_| "Failed to revoke URL".to_string())?;
+    // the backend zips the parts so they download as one file
+    let download_zip = move |_| {
+        let file_name = output_name.get_untracked();
+        let files: Vec<ZipEntry> = parts
+            .get_untracked()
+            .into_iter()
+            .map(|part| ZipEntry {
+                name: part_name(&file_name, &part.key),
+                output: part.output,
+            })
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+        let zip_name = match file_name.split_once('.') {
+            Some((stem, _)) => format!("{}.zip", stem),
+            None => format!("{}.zip", file_name),
+        };
 
-                Ok(())
-            })();
+        spawn_local(async move {
+            let Ok(args) = serde_wasm_bindgen::to_value(&ZipArgs { files }) else {
+                return;
+            };
+            let result = match invoke("zip_parts", args).await {
+                Ok(bytes) => {
+                    let array = js_sys::Array::new();
+                    array.push(&js_sys::Uint8Array::new(&bytes));
+                    web_sys::Blob::new_with_u8_array_sequence(&array)
+                        .map_err(|_| "Failed to create blob".to_string())
+                        .and_then(|blob| save_blob(&blob, &zip_name))
+                }
+                Err(e) => Err(e.as_string().unwrap_or_else(|| "zipping failed".to_string())),
+            };
 
             if let Err(e) = result {
                 set_upload_status.set(Some(format!("[ERR] Download failed: {}", e)));
@@ -286,7 +821,7 @@ pub fn App() -> impl IntoView {
                         "OHH to PokerStars Converter"
                     </h1>
                     <p class="text-xl text-gray-600 dark:text-gray-300">
-                        "Convert .ohh poker hand history files to PokerStars format for GTO Wizard"
+                        "Convert .ohh poker hand history files to PokerStars format for GTO Wizard, or PokerStars text back to .ohh"
                     </p>
                 </div>
 
@@ -296,7 +831,14 @@ pub fn App() -> impl IntoView {
                     </h2>
 
                     <div
-                        class="border-2 border-dashed rounded-lg p-12 text-center transition-all duration-200 cursor-pointer border-gray-300 dark:border-gray-600 hover:border-blue-400 dark:hover:border-blue-500"
+                        class=move || {
+                            let base = "border-2 border-dashed rounded-lg p-12 text-center transition-all duration-200 cursor-pointer";
+                            if is_dragging.get() {
+                                format!("{} border-blue-500 bg-blue-50 dark:bg-blue-900/20", base)
+                            } else {
+                                format!("{} border-gray-300 dark:border-gray-600 hover:border-blue-400 dark:hover:border-blue-500", base)
+                            }
+                        }
                         on:dragover=on_drag_over
                         on:dragleave=on_drag_leave
                         on:drop=on_drop
@@ -331,6 +873,161 @@ pub fn App() -> impl IntoView {
                         </div>
                     </div>
 
+                    <label class="mt-4 flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                        <input
+                            type="checkbox"
+                            class="rounded"
+                            prop:checked=move || strict.get()
+                            on:change=move |ev| set_strict.set(event_target_checked(&ev))
+                        />
+                        "Strict mode: skip hands whose bets, stacks or pots do not add up"
+                    </label>
+
+                    <label class="mt-2 flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                        "Output format"
+                        <select
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                            prop:value=move || dialect.get()
+                            on:change=move |ev| set_dialect.set(event_target_value(&ev))
+                        >
+                            <option value="pokerstars">"PokerStars"</option>
+                            <option value="ggpoker">"GGPoker"</option>
+                            <option value="fulltilt">"Full Tilt"</option>
+                            <option value="ohh">"OHH (normalized)"</option>
+                        </select>
+                    </label>
+
+                    <label class="mt-2 flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                        "Hero"
+                        <input
+                            type="text"
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                            placeholder="name, display name, id or auto"
+                            prop:value=move || hero.get()
+                            on:input=move |ev| set_hero.set(event_target_value(&ev))
+                        />
+                    </label>
+
+                    <div class="mt-2 flex flex-wrap items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+                        <label class="flex items-center gap-2">
+                            "Player names"
+                            <select
+                                class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                                prop:value=move || names.get()
+                                on:change=move |ev| set_names.set(event_target_value(&ev))
+                            >
+                                <option value="name">"Screen name"</option>
+                                <option value="display">"Display name"</option>
+                                <option value="display-or-name">"Display name, else screen name"</option>
+                            </select>
+                        </label>
+                        <button
+                            class="bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 text-gray-900 dark:text-white py-1 px-3 rounded-lg transition-colors"
+                            on:click=pick_aliases
+                        >
+                            "Alias File"
+                        </button>
+                        {move || alias_status.get()}
+                    </div>
+
+                    <div class="mt-2 flex flex-wrap items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+                        <label class="flex items-center gap-2">
+                            <input
+                                type="checkbox"
+                                class="rounded"
+                                prop:checked=move || anonymize.get()
+                                on:change=move |ev| set_anonymize.set(event_target_checked(&ev))
+                            />
+                            "Anonymize player and table names"
+                        </label>
+                        <select
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                            prop:value=move || pseudonyms.get()
+                            prop:disabled=move || !anonymize.get()
+                            on:change=move |ev| set_pseudonyms.set(event_target_value(&ev))
+                        >
+                            <option value="sequential">"Player1, Player2, ..."</option>
+                            <option value="hashed">"Hashed"</option>
+                        </select>
+                        <input
+                            type="text"
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                            placeholder="salt"
+                            prop:value=move || salt.get()
+                            prop:disabled=move || !anonymize.get() || pseudonyms.get() != "hashed"
+                            on:input=move |ev| set_salt.set(event_target_value(&ev))
+                        />
+                        <label class="flex items-center gap-2">
+                            <input
+                                type="checkbox"
+                                class="rounded"
+                                prop:checked=move || keep_hero.get()
+                                prop:disabled=move || !anonymize.get()
+                                on:change=move |ev| set_keep_hero.set(event_target_checked(&ev))
+                            />
+                            "Keep hero's name"
+                        </label>
+                    </div>
+
+                    <div class="mt-2 flex flex-wrap items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+                        <label class="flex flex-1 items-center gap-2">
+                            "Filter"
+                            <input
+                                type="text"
+                                class="flex-1 rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1 font-mono"
+                                placeholder="saw flop and pot > 50bb and not player Bob"
+                                prop:value=move || filter.get()
+                                on:input=move |ev| {
+                                    set_filter.set(event_target_value(&ev));
+                                    set_filter_status.set(None);
+                                }
+                            />
+                        </label>
+                        <button
+                            class="bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 text-gray-900 dark:text-white py-1 px-3 rounded-lg transition-colors"
+                            on:click=count_filter
+                        >
+                            "Count Matches"
+                        </button>
+                        {move || filter_status.get()}
+                    </div>
+
+                    <div class="mt-2 flex flex-wrap items-center gap-3 text-sm text-gray-700 dark:text-gray-300">
+                        <label class="flex items-center gap-2">
+                            "Split output"
+                            <select
+                                class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                                prop:value=move || split_by.get()
+                                on:change=move |ev| set_split_by.set(event_target_value(&ev))
+                            >
+                                <option value="">"No grouping"</option>
+                                <option value="table">"One file per table"</option>
+                                <option value="session">"One file per session"</option>
+                                <option value="day">"One file per day"</option>
+                            </select>
+                        </label>
+                        <label class="flex items-center gap-2">
+                            "Max hands"
+                            <input
+                                type="number"
+                                min="0"
+                                class="w-24 rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                                prop:value=move || max_hands.get()
+                                on:input=move |ev| set_max_hands.set(event_target_value(&ev))
+                            />
+                        </label>
+                        <label class="flex items-center gap-2">
+                            "Max MB"
+                            <input
+                                type="number"
+                                min="0"
+                                class="w-24 rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-2 py-1"
+                                prop:value=move || max_mb.get()
+                                on:input=move |ev| set_max_mb.set(event_target_value(&ev))
+                            />
+                        </label>
+                    </div>
+
                     {move || selected_file_info.get().map(|file_info| {
                         view! {
                             <div class="mt-6 p-4 bg-gray-50 dark:bg-gray-700 rounded-lg">
@@ -378,6 +1075,112 @@ pub fn App() -> impl IntoView {
                     })}
                 </div>
 
+                {move || (!warnings.get().is_empty()).then(|| {
+                    let list = warnings.get();
+                    view! {
+                        <div class="bg-white dark:bg-gray-800 rounded-lg shadow-lg p-8 mb-8">
+                            <h2 class="text-2xl font-bold text-gray-900 dark:text-white mb-4">
+                                {format!("Warnings ({})", list.len())}
+                            </h2>
+                            <ul class="space-y-1 overflow-auto max-h-64 text-sm text-yellow-800 dark:text-yellow-300">
+                                {list.into_iter().map(|w| view! { <li>{w}</li> }).collect_view()}
+                            </ul>
+                        </div>
+                    }
+                })}
+
+                <div class="bg-white dark:bg-gray-800 rounded-lg shadow-lg p-8 mb-8">
+                    <h2 class="text-2xl font-bold text-gray-900 dark:text-white mb-2">
+                        "Convert Many Files"
+                    </h2>
+                    <p class="text-sm text-gray-600 dark:text-gray-400 mb-4">
+                        "Pick several files or a whole folder. Without an output folder the results are combined below for download."
+                    </p>
+                    <div class="flex flex-wrap gap-2">
+                        <button class="bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 text-gray-900 dark:text-white font-semibold py-2 px-4 rounded-lg transition-colors" on:click=pick_batch_files>
+                            "Choose Files"
+                        </button>
+                        <button class="bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 text-gray-900 dark:text-white font-semibold py-2 px-4 rounded-lg transition-colors" on:click=pick_batch_folder>
+                            "Choose Folder"
+                        </button>
+                        <button class="bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 text-gray-900 dark:text-white font-semibold py-2 px-4 rounded-lg transition-colors" on:click=pick_batch_output>
+                            "Output Folder"
+                        </button>
+                    </div>
+                    <p class="mt-3 text-sm text-gray-700 dark:text-gray-300">
+                        {move || match batch_paths.get().len() {
+                            0 => "Nothing selected".to_string(),
+                            1 => batch_paths.get()[0].clone(),
+                            n => format!("{} files selected", n),
+                        }}
+                        {move || batch_output.get().map(|dir| format!(" -> {}", dir))}
+                    </p>
+                    <label class="mt-2 flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                        <input
+                            type="checkbox"
+                            class="rounded"
+                            prop:checked=move || batch_combine.get()
+                            on:change=move |ev| set_batch_combine.set(event_target_checked(&ev))
+                        />
+                        "Write one combined file instead of one file per input"
+                    </label>
+                    <button
+                        class="mt-4 bg-blue-600 hover:bg-blue-700 disabled:bg-gray-400 text-white font-semibold py-2 px-6 rounded-lg transition-colors"
+                        on:click=run_batch
+                        disabled=move || batch_paths.get().is_empty() || is_converting.get()
+                    >
+                        {move || match batch_progress.get() {
+                            Some((done, total)) if total > 0 => format!("Converting {}/{}...", done, total),
+                            Some(_) => "Converting...".to_string(),
+                            None => "Convert All".to_string(),
+                        }}
+                    </button>
+                    {move || (!batch_log.get().is_empty()).then(|| view! {
+                        <ul class="mt-4 space-y-1 overflow-auto max-h-48 text-sm font-mono text-gray-700 dark:text-gray-300">
+                            {batch_log.get().into_iter().map(|line| view! { <li>{line}</li> }).collect_view()}
+                        </ul>
+                    })}
+                </div>
+
+                <div class="bg-white dark:bg-gray-800 rounded-lg shadow-lg p-8 mb-8">
+                    <h2 class="text-2xl font-bold text-gray-900 dark:text-white mb-2">
+                        "Watch a Folder"
+                    </h2>
+                    <p class="text-sm text-gray-600 dark:text-gray-400 mb-4">
+                        "New hands written to the folder are converted and appended to the output folder as they arrive."
+                    </p>
+                    <div class="grid gap-3 sm:grid-cols-2">
+                        <input
+                            type="text"
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-3 py-2 text-sm"
+                            placeholder="Hand history folder"
+                            prop:value=move || watch_input.get()
+                            prop:disabled=move || is_watching.get()
+                            on:input=move |ev| set_watch_input.set(event_target_value(&ev))
+                        />
+                        <input
+                            type="text"
+                            class="rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 px-3 py-2 text-sm"
+                            placeholder="Output folder"
+                            prop:value=move || watch_output.get()
+                            prop:disabled=move || is_watching.get()
+                            on:input=move |ev| set_watch_output.set(event_target_value(&ev))
+                        />
+                    </div>
+                    <button
+                        class="mt-4 bg-blue-600 hover:bg-blue-700 disabled:bg-gray-400 text-white font-semibold py-2 px-6 rounded-lg transition-colors"
+                        on:click=toggle_watch
+                        disabled=move || watch_input.get().is_empty() || watch_output.get().is_empty()
+                    >
+                        {move || if is_watching.get() { "Stop Watching" } else { "Start Watching" }}
+                    </button>
+                    {move || (!watch_log.get().is_empty()).then(|| view! {
+                        <ul class="mt-4 space-y-1 overflow-auto max-h-48 text-sm font-mono text-gray-700 dark:text-gray-300">
+                            {watch_log.get().into_iter().map(|line| view! { <li>{line}</li> }).collect_view()}
+                        </ul>
+                    })}
+                </div>
+
                 {move || (!converted_content.get().is_empty()).then(|| {
                     view! {
                         <div class="bg-white dark:bg-gray-800 rounded-lg shadow-lg p-8">
@@ -392,6 +1195,11 @@ pub fn App() -> impl IntoView {
                                     <button class="bg-blue-600 hover:bg-blue-700 text-white font-semibold py-2 px-4 rounded-lg transition-colors" on:click=download_file>
                                         "Download"
                                     </button>
+                                    {move || (!parts.get().is_empty()).then(|| view! {
+                                        <button class="bg-blue-600 hover:bg-blue-700 text-white font-semibold py-2 px-4 rounded-lg transition-colors" on:click=download_zip>
+                                            {format!("Download ZIP ({} parts)", parts.get().len())}
+                                        </button>
+                                    })}
                                 </div>
                             </div>
                             <pre class="bg-gray-50 dark:bg-gray-900 border border-gray-200 dark:border-gray-700 rounded-lg p-4 overflow-auto max-h-96 text-sm text-gray-800 dark:text-gray-200">